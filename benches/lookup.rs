@@ -0,0 +1,42 @@
+//! Measures option lookups on a command line with many parsed options.
+//!
+//! Run with `cargo bench --bench lookup`.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use anpcli::{AnpOption, DefaultParser, Options, Parser};
+
+const OPTION_COUNT: usize = 500;
+const ROUNDS: usize = 200;
+
+fn main() {
+    let mut options = Options::new();
+    let mut arguments = Vec::new();
+    for i in 0..OPTION_COUNT {
+        options.add_option(AnpOption::builder()
+            .long_option(&format!("option-{}", i))
+            .number_of_args(1)
+            .build()
+            .unwrap());
+        arguments.push(format!("--option-{}", i));
+        arguments.push(i.to_string());
+    }
+
+    let mut parser = DefaultParser::builder().build();
+    let cmd = parser.parse_args(&options, &arguments).unwrap();
+    let names: Vec<String> = (0..OPTION_COUNT).map(|i| format!("option-{}", i)).collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for name in names.iter() {
+            black_box(cmd.has_option(black_box(name)));
+            black_box(cmd.get_value::<usize>(black_box(name)));
+        }
+    }
+    let elapsed = start.elapsed();
+    let lookups = ROUNDS * OPTION_COUNT * 2;
+
+    println!("{} lookups on {} options: {:?} total, {:?} per lookup",
+             lookups, OPTION_COUNT, elapsed, elapsed / lookups as u32);
+}
@@ -1,13 +1,26 @@
 use std::any::type_name;
 use std::cell::{Ref, RefCell};
 use std::collections::HashMap;
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 use std::ops::Deref;
 use std::process::exit;
 use std::rc::Rc;
 use std::str::FromStr;
 
-use crate::option::AnpOption;
+use crate::error::ParseErr;
+use crate::option::{AnpOption, OptionGroup, Required};
+use crate::util::Util;
+
+/// Where the value of an option came from, see [`CommandLine::get_value_source`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueSource {
+    /// The option is passed in command line.
+    CommandLine,
+    /// The option is read from an environment variable, see [`crate::ParserBuilder::set_env_prefix`].
+    Env,
+    /// The option is the default value, see [`crate::OptionBuilder::default_value`].
+    Default,
+}
 
 /// The `CommandLine` is the struct holding all parsed options and arguments.
 ///
@@ -26,7 +39,20 @@ use crate::option::AnpOption;
 #[derive(Debug)]
 pub struct CommandLine {
     args: Vec<String>,
+    arg_indices: Vec<usize>,
     options: Vec<Rc<RefCell<AnpOption>>>,
+    option_index: HashMap<String, usize>,
+    // the selected option key of the group, keyed by the names of each group member
+    group_selections: HashMap<String, String>,
+    // the source of options not passed in command line, keyed by option key
+    value_sources: HashMap<String, ValueSource>,
+    unknown_options: Vec<String>,
+    program_name: Option<String>,
+    // the exit code of the exiting accessors
+    exit_code: i32,
+    stopped_at: Option<usize>,
+    // the required options and groups of the parsed `Options`
+    requirements: Vec<Required>,
 }
 
 pub struct CmdBuilder {
@@ -47,30 +73,343 @@ impl CmdBuilder {
         self.command_line.add_option(opt);
         self
     }
+
+    pub fn program_name(mut self, program_name: &str) -> Self {
+        self.command_line.set_program_name(program_name);
+        self
+    }
+
+    /// See [`CommandLine::set_exit_code`].
+    pub fn exit_code(mut self, code: i32) -> Self {
+        self.command_line.set_exit_code(code);
+        self
+    }
 }
 
 impl CommandLine {
     pub fn builder() -> CmdBuilder {
         CmdBuilder {
-            command_line: CommandLine { args: vec![], options: vec![] },
+            command_line: CommandLine {
+                args: vec![],
+                arg_indices: vec![],
+                options: vec![],
+                option_index: HashMap::new(),
+                group_selections: HashMap::new(),
+                value_sources: HashMap::new(),
+                unknown_options: vec![],
+                program_name: None,
+                exit_code: 1,
+                stopped_at: None,
+                requirements: vec![],
+            },
         }
     }
 
     pub fn add_arg(&mut self, arg: &str) {
+        self.add_arg_at(arg, self.args.len());
+    }
+
+    /// Add the argument `arg` which is at `index` of the parsed arguments.
+    pub fn add_arg_at(&mut self, arg: &str, index: usize) {
         self.args.push(arg.to_owned());
+        self.arg_indices.push(index);
     }
 
     pub fn add_option(&mut self, option: Rc<RefCell<AnpOption>>) {
+        let index = self.options.len();
+        {
+            let opt = option.borrow();
+            // keep the first added option when multiple options share a name
+            for name in opt.get_opt().into_iter().chain(opt.get_long_opt()) {
+                self.option_index.entry(name.to_owned()).or_insert(index);
+            }
+        }
         self.options.push(option);
     }
 
+    pub fn add_unknown_option(&mut self, option: &str) {
+        self.unknown_options.push(option.to_owned());
+    }
+
+    /// Get the unrecognized options in command-line order, which are collected only if
+    /// [`crate::ParserBuilder::set_collect_unknown`] is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option1("v", "verbose").unwrap();
+    ///
+    /// let mut parser = DefaultParser::builder().set_collect_unknown(true).build();
+    /// let cmd = parser.parse_args(&options, &["-x", "-v", "--color=auto", "file"]).unwrap();
+    /// assert!(cmd.has_option("v"));
+    /// assert_eq!(vec!["-x", "--color=auto"], cmd.get_unknown_options());
+    /// assert_eq!(vec!["file"], cmd.get_arg_list());
+    /// ```
+    pub fn get_unknown_options(&self) -> Vec<&str> {
+        self.unknown_options.iter().map(|o| o.as_str()).collect()
+    }
+
+    /// Record the `selected` option key of the option `group`.
+    pub fn add_group_selection(&mut self, group: &OptionGroup, selected: &str) {
+        for member in group.get_options() {
+            let member = member.borrow();
+            for name in member.get_opt().into_iter().chain(member.get_long_opt()) {
+                self.group_selections.insert(name.to_owned(), selected.to_owned());
+            }
+        }
+    }
+
+    /// Get the key of the selected option in the option group, which the `group_key`
+    /// option is a member of. The `group_key` is the short or long name of any member.
+    ///
+    /// Returns [`None`] if no member of the group is passed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, OptionGroup, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option_group(OptionGroup::new()
+    ///     .add_option(AnpOption::builder().long_option("json").build().unwrap())
+    ///     .add_option(AnpOption::builder().long_option("yaml").build().unwrap()));
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// let cmd = parser.parse_args(&options, &["--yaml"]).unwrap();
+    /// assert_eq!(Some("yaml".to_string()), cmd.get_selected_in_group("json"));
+    /// let cmd = parser.parse_args(&options, &[""; 0]).unwrap();
+    /// assert_eq!(None, cmd.get_selected_in_group("json"));
+    /// ```
+    pub fn get_selected_in_group(&self, group_key: &str) -> Option<String> {
+        self.group_selections.get(group_key).cloned()
+    }
+
+    /// Record the `source` of the option with `key`, for options not passed in command line.
+    pub fn set_value_source(&mut self, key: &str, source: ValueSource) {
+        self.value_sources.insert(key.to_owned(), source);
+    }
+
+    /// Get where the value of `opt` came from, the command line, an environment
+    /// variable or the default value.
+    ///
+    /// Returns [`None`] if no option `opt`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser, ValueSource};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("level").has_arg(true).default_value("info").build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// let cmd = parser.parse_args(&options, &["--level", "debug"]).unwrap();
+    /// assert_eq!(Some(ValueSource::CommandLine), cmd.get_value_source("level"));
+    /// let cmd = parser.parse_args(&options, &[""; 0]).unwrap();
+    /// assert_eq!(Some(ValueSource::Default), cmd.get_value_source("level"));
+    /// ```
+    pub fn get_value_source(&self, opt: &str) -> Option<ValueSource> {
+        let option = self.resolve_option(opt)?;
+        Some(self.value_sources.get(option.get_key()).copied().unwrap_or(ValueSource::CommandLine))
+    }
+
+    /// Set the required options and option groups, see [`Self::requirement_report`].
+    pub fn set_requirements(&mut self, requirements: Vec<Required>) {
+        self.requirements = requirements;
+    }
+
+    /// Report how each required option and option group is satisfied, in the order
+    /// they were added to the [`crate::Options`], for debugging the precedence of
+    /// command line, environment variables and defaults.
+    ///
+    /// A group is named like `(-a | -b)` and reports the source of its selected option.
+    /// The source is [`None`] if the requirement isn't satisfied, which only happens
+    /// when an eager option skips the checks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, OptionGroup, Options, Parser, ValueSource};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("l").long_option("level").has_arg(true).required(true)
+    ///     .default_value("info").build().unwrap());
+    /// options.add_required_option("o", "output", true, "output file").unwrap();
+    ///
+    /// options.add_option_group(OptionGroup::new()
+    ///     .required(true)
+    ///     .add_option(AnpOption::builder().option("b").build().unwrap())
+    ///     .add_option(AnpOption::builder().option("a").build().unwrap()));
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-o", "a.txt", "-b"]).unwrap();
+    /// assert_eq!(vec![("l".to_string(), Some(ValueSource::Default)),
+    ///                 ("o".to_string(), Some(ValueSource::CommandLine)),
+    ///                 ("(-a | -b)".to_string(), Some(ValueSource::CommandLine))],
+    ///            cmd.requirement_report());
+    /// ```
+    pub fn requirement_report(&self) -> Vec<(String, Option<ValueSource>)> {
+        self.requirements.iter().map(|required| {
+            let (name, key) = match required {
+                Required::OptKey(key) => (key.to_owned(), Some(key.to_owned()).filter(|key| self.has_option(key))),
+                Required::OptGroup(group) => (group.borrow().member_names(), group.borrow().get_options().first()
+                    .and_then(|member| self.get_selected_in_group(member.borrow().get_key()))),
+            };
+            (name, key.and_then(|key| self.get_value_source(&key)))
+        }).collect()
+    }
+
+    /// Get the keys of the options passed in command line in order, excluding the
+    /// options from environment variables and defaults, see [`Self::get_value_source`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("level").has_arg(true).default_value("info").build().unwrap());
+    /// options.add_option(AnpOption::builder().long_option("color").has_arg(true).default_value("auto").build().unwrap());
+    /// options.add_option2("o", "output", true, "output file").unwrap();
+    /// options.add_option2("v", "verbose", false, "print more messages").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-v", "--color", "never", "-v"]).unwrap();
+    /// assert_eq!(vec!["v", "color"], cmd.explicit_options());
+    /// assert!(cmd.has_option("level"));
+    /// ```
+    pub fn explicit_options(&self) -> Vec<String> {
+        let mut keys: Vec<String> = vec![];
+        for option in self.options.iter() {
+            let key = option.borrow().get_key().to_owned();
+            if !self.value_sources.contains_key(&key) && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    /// Set the exit code of the exiting accessors like [`Self::get_expected_value`],
+    /// which is 1 by default, see [`crate::ParserBuilder::set_usage_exit_code`].
+    pub fn set_exit_code(&mut self, code: i32) {
+        self.exit_code = code;
+    }
+
+    /// Get the exit code of the exiting accessors.
+    pub fn get_exit_code(&self) -> i32 {
+        self.exit_code
+    }
+
+    /// Set the index of the argument where option parsing stopped, see [`Self::stopped_at`].
+    pub fn set_stopped_at(&mut self, index: usize) {
+        self.stopped_at = Some(index);
+    }
+
+    /// Get the index of the non-option argument where option parsing stopped with
+    /// [`crate::ParserBuilder::set_stop_at_non_option`], or [`None`] if all arguments
+    /// were parsed as options. A `--` doesn't count as a stop.
+    ///
+    /// Like [`Self::get_args_with_index`], the index counts the program name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option1("v", "verbose").unwrap();
+    /// options.add_option1("x", "extract").unwrap();
+    ///
+    /// let mut parser = DefaultParser::builder().set_stop_at_non_option(true).build();
+    /// let cmd = parser.parse_args(&options, &["-v", "file", "-x"]).unwrap();
+    /// assert_eq!(Some(1), cmd.stopped_at());
+    /// assert_eq!(None, parser.parse_args(&options, &["-v", "-x"]).unwrap().stopped_at());
+    /// ```
+    pub fn stopped_at(&self) -> Option<usize> {
+        self.stopped_at
+    }
+
+    /// Set the program name, which is also inserted as the first argument.
+    pub fn set_program_name(&mut self, program_name: &str) {
+        if self.program_name.is_some() {
+            self.args.remove(0);
+            self.arg_indices.remove(0);
+        }
+        self.program_name = Some(program_name.to_owned());
+        self.args.insert(0, program_name.to_owned());
+        self.arg_indices.insert(0, 0);
+    }
+
     /// Get additional arguments that are not captured by any options.
     ///
     /// The first arguments is typically the filename of the executable.
+    /// Also see [`Self::get_args_without_program`].
     pub fn get_arg_list(&self) -> Vec<&str> {
         self.args.iter().map(|a| a.as_str()).collect()
     }
 
+    /// Get the positional argument at `index`, excluding the program name.
+    ///
+    /// Returns [`None`] if `index` is out of range.
+    pub fn positional(&self, index: usize) -> Option<&str> {
+        self.get_args_without_program().get(index).copied()
+    }
+
+    /// Get the positional arguments from `start`, excluding the program name.
+    ///
+    /// An empty slice is returned if `start` is out of range, so it never panics
+    /// unlike slicing [`Self::get_arg_list`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&Options::new(), &["cp", "a", "b"]).unwrap();
+    /// assert_eq!(Some("a"), cmd.positional(1));
+    /// assert_eq!(None, cmd.positional(3));
+    /// assert_eq!(["a", "b"], cmd.positional_range(1));
+    /// assert!(cmd.positional_range(5).is_empty());
+    /// ```
+    pub fn positional_range(&self, start: usize) -> &[String] {
+        let skip = if self.program_name.is_some() { 1 } else { 0 };
+        self.args.get(start.saturating_add(skip)..).unwrap_or(&[])
+    }
+
+    /// Get the arguments with their indices in the parsed arguments, like
+    /// [`Self::get_arg_list`] the program name comes first with index 0 if exists.
+    ///
+    /// The index counts the program name, so it's the position in `env::args()` for
+    /// [`crate::Parser::parse`]. If argument files are expanded, the index is the position
+    /// after the expansion.
+    pub fn get_args_with_index(&self) -> Vec<(usize, &str)> {
+        self.arg_indices.iter().copied().zip(self.args.iter().map(|a| a.as_str())).collect()
+    }
+
+    /// Get additional arguments that are not captured by any options, excluding
+    /// the program name.
+    pub fn get_args_without_program(&self) -> Vec<&str> {
+        let skip = if self.program_name.is_some() { 1 } else { 0 };
+        self.args.iter().skip(skip).map(|a| a.as_str()).collect()
+    }
+
+    /// Check whether any argument is `-`, which conventionally means reading from stdin.
+    ///
+    /// A single `-` is never considered an option, so it's also accepted as an option
+    /// value, like `-o -`, which is not counted here.
+    pub fn has_stdin_arg(&self) -> bool {
+        self.get_args_without_program().contains(&"-")
+    }
+
+    /// Get the program name, which is the first argument from `env::args()`.
+    ///
+    /// It's [`None`] if the arguments are passed to [`crate::Parser::parse_args`] without
+    /// [`crate::ParserBuilder::set_skip_program_name`] enabled.
+    pub fn get_program_name(&self) -> Option<&str> {
+        self.program_name.as_deref()
+    }
+
     fn get_option_properties_inner(&self, option: &AnpOption) -> HashMap<String, String> {
         let mut properties = HashMap::new();
 
@@ -98,21 +437,100 @@ impl CommandLine {
     /// results in `{"a", "true"}`. Note that if the values are more than 2,
     /// remaining values are ignored.
     pub fn get_option_properties(&self, option: &str) -> HashMap<String, String> {
-        for processed_option in self.options.iter() {
-            let p_opt = &processed_option.borrow();
-            if p_opt.get_opt().map(|o| o as &str) == Some(option)
-                || p_opt.get_long_opt().map(|o| o as &str) == Some(option) {
-                return self.get_option_properties_inner(&p_opt);
-            }
+        match self.resolve_option(option) {
+            Some(p_opt) => self.get_option_properties_inner(&p_opt),
+            None => HashMap::new(),
         }
-        HashMap::new()
     }
 
     /// Get all [`AnpOption`] that passed to the command line.
+    ///
+    /// The options are in the order they appeared on the command line, and an option
+    /// passed multiple times has an entry for each occurrence. Options set from default
+    /// values are included and come last.
     pub fn get_options(&self) -> Vec<Ref<AnpOption>> {
         self.options.iter().map(|o| o.borrow()).collect()
     }
 
+    /// Get all [`AnpOption`] that passed to the command line, in the order they appeared
+    /// on the command line. Options set from default values come last.
+    ///
+    /// This is the same as [`Self::get_options`], named for callers that rely on the order.
+    pub fn get_options_in_order(&self) -> Vec<Ref<'_, AnpOption>> {
+        self.get_options()
+    }
+
+    /// Iterate the parsed options as `(key, value)` pairs in the order they were parsed.
+    ///
+    /// An option with multiple values yields a pair for each value, and an option without
+    /// value yields a single pair with value [`None`]. The key is the short option name if
+    /// exists, otherwise the long option name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option0("D", true, "define a macro").unwrap();
+    /// options.add_option0("v", false, "print verbosely").unwrap();
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// let cmd = parser.parse_args(&options, &["-DA", "-v", "-DB"]).unwrap();
+    /// let parsed: Vec<(String, Option<String>)> = cmd.iter_parsed().collect();
+    /// assert_eq!(vec![
+    ///     ("D".to_string(), Some("A".to_string())),
+    ///     ("v".to_string(), None),
+    ///     ("D".to_string(), Some("B".to_string())),
+    /// ], parsed);
+    /// ```
+    pub fn iter_parsed(&self) -> impl Iterator<Item=(String, Option<String>)> + '_ {
+        self.options.iter().flat_map(|option| {
+            let option = option.borrow();
+            let key = option.get_key().to_owned();
+            let values: Vec<String> = option.get_values().into_iter().map(|v| v.unwrap()).collect();
+            if values.is_empty() {
+                vec![(key, None)]
+            } else {
+                values.into_iter().map(|v| (key.to_owned(), Some(v))).collect()
+            }
+        })
+    }
+
+    /// Collect the parsed options into a map from the option key to its raw values.
+    ///
+    /// The key is the short option name if exists, otherwise the long option name. The
+    /// values of an option passed multiple times are concatenated in order. A flag
+    /// appears with an empty `Vec`, so a present flag can be told apart from an absent
+    /// one, which has no entry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option0("D", true, "define a macro").unwrap();
+    /// options.add_option2("v", "verbose", false, "print verbosely").unwrap();
+    /// options.add_option0("q", false, "print nothing").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-DA", "--verbose", "-DB"]).unwrap();
+    /// let map = cmd.to_value_map();
+    /// assert_eq!(Some(&vec!["A".to_string(), "B".to_string()]), map.get("D"));
+    /// assert_eq!(Some(&vec![]), map.get("v"));
+    /// assert_eq!(None, map.get("q"));
+    /// ```
+    pub fn to_value_map(&self) -> HashMap<String, Vec<String>> {
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for option in self.options.iter() {
+            let option = option.borrow();
+            map.entry(option.get_key().to_owned())
+                .or_default()
+                .extend(option.get_values().into_iter().map(|v: Result<String, _>| v.unwrap()));
+        }
+        map
+    }
+
     /// Get parsed option value in requested type.
     ///
     /// [`None`] is returned if no option `opt` or `opt` has no value.
@@ -127,6 +545,232 @@ impl CommandLine {
         option.get_value()
     }
 
+    /// Get the first parsed value of `opt` in requested type, across all occurrences
+    /// of the option.
+    ///
+    /// Unlike [`CommandLine::get_value`], an occurrence without value like an optional
+    /// argument not passed is skipped. If a value separator is set, the values are
+    /// counted after splitting, so the first value of `-Da,b` is `a`.
+    pub fn get_first_value<T: FromStr>(&self, opt: &str) -> Option<Result<T, T::Err>> {
+        let key = self.resolve_option(opt)?.get_key().to_owned();
+        self.options.iter()
+            .map(|option| option.borrow())
+            .filter(|option| option.get_key() == key)
+            .find_map(|option| option.get_value())
+    }
+
+    /// Get the last parsed value of `opt` in requested type, across all occurrences
+    /// of the option, for the "last one wins" overriding.
+    ///
+    /// If a value separator is set, the values are counted after splitting, so the
+    /// last value of `-Da,b` is `b`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("l", "level", true, "log level").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-l", "info", "--level", "debug"]).unwrap();
+    /// assert_eq!(Some(Ok("info".to_string())), cmd.get_first_value("l"));
+    /// assert_eq!(Some(Ok("debug".to_string())), cmd.get_last_value("l"));
+    /// ```
+    pub fn get_last_value<T: FromStr>(&self, opt: &str) -> Option<Result<T, T::Err>> {
+        let key = self.resolve_option(opt)?.get_key().to_owned();
+        self.options.iter().rev()
+            .map(|option| option.borrow())
+            .filter(|option| option.get_key() == key)
+            .find_map(|option| option.get_values().pop())
+    }
+
+    /// Get every value of `opt` across all occurrences in command-line order, so the
+    /// overridden values can be audited while [`Self::get_last_value`] is the effective one.
+    ///
+    /// [`Self::get_value`] still returns the value of the first occurrence.
+    /// An empty `Vec` is returned if no option `opt` or `opt` has no value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("o", "output", true, "output file").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-o", "a", "-o", "b", "--output", "c"]).unwrap();
+    /// assert_eq!(vec!["a", "b", "c"], cmd.get_override_history("o"));
+    /// assert_eq!(Some(Ok("c".to_string())), cmd.get_last_value("output"));
+    /// assert!(cmd.get_override_history("x").is_empty());
+    /// ```
+    pub fn get_override_history(&self, opt: &str) -> Vec<String> {
+        let key = match self.resolve_option(opt) {
+            Some(option) => option.get_key().to_owned(),
+            None => return vec![],
+        };
+        self.options.iter()
+            .map(|option| option.borrow())
+            .filter(|option| option.get_key() == key)
+            .flat_map(|option| option.get_values().into_iter().map(|v: Result<String, _>| v.unwrap()).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Get parsed option value in requested type, converting the error to [`ParseErr`].
+    ///
+    /// `Ok(None)` is returned if no option `opt` or `opt` has no value, and
+    /// [`ParseErr::ValueConversion`] is returned if the value can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, ParseErr, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("t", "threads", true, "number of threads").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-t", "x"]).unwrap();
+    /// assert!(matches!(cmd.get_parsed::<u32>("t"), Err(ParseErr::ValueConversion { .. })));
+    /// assert_eq!(Ok(None), cmd.get_parsed::<u32>("v"));
+    /// ```
+    pub fn get_parsed<T: FromStr>(&self, opt: &str) -> Result<Option<T>, ParseErr>
+        where T::Err: Display {
+        let value = match self.get_value::<String>(opt) {
+            Some(value) => value.unwrap(),
+            None => return Ok(None),
+        };
+        Self::convert_value(opt, value).map(Some)
+    }
+
+    fn convert_value<T: FromStr>(opt: &str, value: String) -> Result<T, ParseErr>
+        where T::Err: Display {
+        T::from_str(&value).map_err(|e| ParseErr::ValueConversion {
+            option: opt.to_owned(),
+            value,
+            type_name: type_name::<T>().to_owned(),
+            reason: e.to_string(),
+        })
+    }
+
+    /// Get the option value as a boolean, accepting `true/false`, `yes/no`, `on/off` and
+    /// `1/0`, ignoring case.
+    ///
+    /// [`None`] is returned if no option `opt`, `opt` has no value or the value is
+    /// not a boolean, see [`CommandLine::try_get_bool`] for the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("c", "color", true, "colorize output").unwrap();
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert_eq!(Some(true), parser.parse_args(&options, &["-c", "Yes"]).unwrap().get_bool("c"));
+    /// assert_eq!(Some(false), parser.parse_args(&options, &["-c", "off"]).unwrap().get_bool("c"));
+    /// assert_eq!(None, parser.parse_args(&options, &["-c", "maybe"]).unwrap().get_bool("c"));
+    /// ```
+    pub fn get_bool(&self, opt: &str) -> Option<bool> {
+        self.try_get_bool(opt).ok().flatten()
+    }
+
+    /// Get the option value as a boolean like [`CommandLine::get_bool`], returning
+    /// [`ParseErr::ValueConversion`] if the value is not a boolean.
+    pub fn try_get_bool(&self, opt: &str) -> Result<Option<bool>, ParseErr> {
+        let value = match self.get_value::<String>(opt) {
+            Some(value) => value.unwrap(),
+            None => return Ok(None),
+        };
+        match Util::parse_bool(&value) {
+            Some(flag) => Ok(Some(flag)),
+            None => Err(ParseErr::ValueConversion {
+                option: opt.to_owned(),
+                value,
+                type_name: type_name::<bool>().to_owned(),
+                reason: "expected one of true, false, yes, no, on, off, 1, 0".to_owned(),
+            }),
+        }
+    }
+
+    /// Check the `opt` is specified in command line, for the validation in application.
+    ///
+    /// # Error
+    ///
+    /// Returns [`ParseErr::MissingOption`] if the `opt` is absent.
+    pub fn require(&self, opt: &str) -> Result<(), ParseErr> {
+        if self.has_option(opt) {
+            Ok(())
+        } else {
+            Err(ParseErr::MissingOption(vec![Required::OptKey(opt.to_owned())]))
+        }
+    }
+
+    /// Get parsed option value in requested type, requiring the `opt` to have a value.
+    ///
+    /// # Error
+    ///
+    /// Returns [`ParseErr::MissingOption`] if the `opt` is absent, [`ParseErr::MissingArgument`]
+    /// if it has no value, or [`ParseErr::ValueConversion`] if the value can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, ParseErr, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("t", "threads", true, "number of threads").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-t", "8"]).unwrap();
+    /// assert_eq!(Ok(()), cmd.require("threads"));
+    /// assert_eq!(Ok(8), cmd.require_value::<u32>("t"));
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &[""; 0]).unwrap();
+    /// assert!(matches!(cmd.require("t"), Err(ParseErr::MissingOption(_))));
+    /// assert!(matches!(cmd.require_value::<u32>("t"), Err(ParseErr::MissingOption(_))));
+    /// ```
+    pub fn require_value<T: FromStr>(&self, opt: &str) -> Result<T, ParseErr>
+        where T::Err: Display {
+        self.require(opt)?;
+        match self.get_value::<String>(opt) {
+            Some(value) => Self::convert_value(opt, value.unwrap()),
+            None => Err(ParseErr::MissingArgument(self.resolve_option(opt).unwrap().clone(), None)),
+        }
+    }
+
+    /// Get parsed option value in requested type, or the `default` if the `opt` has
+    /// no value or the value can't be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("t", "threads", true, "number of threads").unwrap();
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert_eq!(8, parser.parse_args(&options, &["-t", "8"]).unwrap().get_value_or("t", 4));
+    /// assert_eq!(4, parser.parse_args(&options, &["-t", "x"]).unwrap().get_value_or("t", 4));
+    /// assert_eq!(4, parser.parse_args(&options, &[""; 0]).unwrap().get_value_or("t", 4));
+    /// ```
+    ///
+    /// Also see [`CommandLine::get_value_or_else`].
+    pub fn get_value_or<T: FromStr>(&self, opt: &str, default: T) -> T {
+        self.get_value_or_else(opt, || default)
+    }
+
+    /// Get parsed option value in requested type, or the result of `default` if the `opt`
+    /// has no value or the value can't be parsed.
+    ///
+    /// Also see [`CommandLine::get_value_or`].
+    pub fn get_value_or_else<T: FromStr, F: FnOnce() -> T>(&self, opt: &str, default: F) -> T {
+        match self.get_value(opt) {
+            Some(Ok(value)) => value,
+            _ => default(),
+        }
+    }
+
     /// Get parsed option values in requested type.
     ///
     /// Empty `Vec` is returned if no option `opt` or `opt` has no value.
@@ -140,10 +784,71 @@ impl CommandLine {
         Some(option.get_values())
     }
 
+    /// Get exactly `N` parsed option values as an array, like `--point X Y` for an option
+    /// with [`crate::OptionBuilder::number_of_args`] of 2.
+    ///
+    /// [`None`] is returned if no option `opt`. If fewer or more than `N` values were
+    /// parsed, [`ParseErr::ProcessingErr`] is returned, and if a value can't be converted,
+    /// [`ParseErr::ValueConversion`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("point").number_of_args(2).build().unwrap());
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["--point", "3", "4"]).unwrap();
+    /// assert_eq!(Some(Ok([3, 4])), cmd.get_value_array::<i32, 2>("point"));
+    /// assert!(cmd.get_value_array::<i32, 3>("point").unwrap().is_err());
+    /// ```
+    pub fn get_value_array<T: FromStr, const N: usize>(&self, opt: &str) -> Option<Result<[T; N], ParseErr>>
+        where T::Err: Display {
+        let values: Vec<String> = self.get_values::<String>(opt)?.into_iter().map(|v| v.unwrap()).collect();
+        if values.len() != N {
+            return Some(Err(ParseErr::ProcessingErr {
+                desc: format!("expected {} values for option '{}', got {}", N, opt, values.len()),
+                source: None,
+            }));
+        }
+
+        let parsed: Result<Vec<T>, ParseErr> = values.into_iter()
+            .map(|value| Self::convert_value(opt, value))
+            .collect();
+        // the length is checked above
+        Some(parsed.map(|parsed| parsed.try_into().ok().unwrap()))
+    }
+
+    /// Get the values of `opt` as they were passed, before splitting by the value separator.
+    ///
+    /// Splitting the raw values by the separator gives the values from
+    /// [`Self::get_values`], see [`AnpOption::get_raw_values`]. [`None`] is returned if
+    /// no option `opt`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("l").has_args().value_separator(',').build().unwrap());
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-l", "a,b", "c"]).unwrap();
+    /// assert_eq!(Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+    ///            cmd.get_values::<String>("l").map(|v| v.into_iter().map(|v| v.unwrap()).collect()));
+    /// assert_eq!(Some(vec!["a,b".to_string(), "c".to_string()]), cmd.get_raw_values("l"));
+    /// ```
+    pub fn get_raw_values(&self, opt: &str) -> Option<Vec<String>> {
+        let option = self.resolve_option(opt)?;
+        Some(option.get_raw_values().into_iter().map(|v| v.to_owned()).collect())
+    }
+
     /// Get parsed option value in requested type or exit.
     ///
     /// The method auto unwrap result from [`CommandLine::get_value`].
-    /// If the result is [`None`] or [`Err`], the program exit with error message.
+    /// If the result is [`None`] or [`Err`], the program exit with error message, see
+    /// [`CommandLine::set_exit_code`] for the exit code.
     ///
     /// Also see [`CommandLine::get_expected_values`].
     pub fn get_expected_value<T: FromStr + Debug>(&self, opt: &str) -> T {
@@ -151,7 +856,7 @@ impl CommandLine {
             self.parse_or_panic(opt, result.unwrap())
         } else {
             eprintln!("error: option '{}' is required", opt);
-            exit(1);
+            exit(self.exit_code);
         }
     }
 
@@ -168,7 +873,7 @@ impl CommandLine {
                 .collect()
         } else {
             eprintln!("error: option '{}' is required", opt);
-            exit(1);
+            exit(self.exit_code);
         }
     }
 
@@ -178,22 +883,99 @@ impl CommandLine {
         } else {
             eprintln!("parse error: unable to parse option '{}', expect type '{}', got '{}'",
                       opt, type_name::<T>(), value);
-            exit(1);
+            exit(self.exit_code);
         }
     }
 
+    /// Get the state of a negatable flag `opt`, see [`crate::OptionBuilder::negatable`].
+    ///
+    /// Returns `Some(true)` if `--opt` is passed, `Some(false)` if `--no-opt` is passed,
+    /// or [`None`] if neither is passed. If both are passed, the last one wins.
+    pub fn get_flag(&self, opt: &str) -> Option<bool> {
+        self.options.iter().rev()
+            .map(|option| option.borrow())
+            .find(|option| option.get_opt().map(|s| s.as_str()) == Some(opt)
+                || option.get_long_opt().map(|s| s.as_str()) == Some(opt))
+            .map(|option| !option.is_negated())
+    }
+
     /// Check if the `opt` is specified in command line.
+    ///
+    /// A negatable option passed as `--no-<opt>` is also specified, so this returns
+    /// `true` for it. Use [`Self::get_flag`] to tell `--opt` from `--no-opt`.
     pub fn has_option(&self, opt: &str) -> bool {
-        self.resolve_option(opt).is_some()
+        self.option_index.contains_key(opt)
+    }
+
+    /// Check if any of the `opts` is specified in command line.
+    ///
+    /// The short and long option names can be mixed in `opts`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("j", "json", false, "output json").unwrap();
+    /// options.add_option2("y", "yaml", false, "output yaml").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["--json"]).unwrap();
+    /// assert!(cmd.has_any(&["j", "yaml"]));
+    /// assert!(!cmd.has_all(&["json", "y"]));
+    /// ```
+    pub fn has_any(&self, opts: &[&str]) -> bool {
+        opts.iter().any(|opt| self.has_option(opt))
+    }
+
+    /// Check if all of the `opts` are specified in command line.
+    ///
+    /// The short and long option names can be mixed in `opts`.
+    pub fn has_all(&self, opts: &[&str]) -> bool {
+        opts.iter().all(|opt| self.has_option(opt))
     }
 
     fn resolve_option(&self, opt: &str) -> Option<Ref<AnpOption>> {
-        for option in self.options.iter() {
-            if option.borrow().get_opt().map(|s| s.as_str()) == Some(opt)
-                || option.borrow().get_long_opt().map(|s| s.as_str()) == Some(opt) {
-                return Some(option.borrow());
+        self.option_index.get(opt).map(|&index| self.options[index].borrow())
+    }
+}
+
+/// A readable summary of the parsed options and arguments, like
+/// `options: {v, f=[a.txt, b.txt]}; args: [x]`.
+///
+/// The options are listed by key in the order they were first parsed, the values of
+/// an option passed multiple times are concatenated, and the program name is excluded
+/// from the arguments.
+///
+/// # Example
+///
+/// ```
+/// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+///
+/// let mut options = Options::new();
+/// options.add_option(AnpOption::builder().long_option("verbose").build().unwrap());
+/// options.add_option(AnpOption::builder().long_option("output").has_arg(true).build().unwrap());
+///
+/// let cmd = DefaultParser::builder().build().parse_args(&options, &["--verbose", "a", "--output", "file.txt", "b"]).unwrap();
+/// assert_eq!("options: {verbose, output=[file.txt]}; args: [a, b]", cmd.to_string());
+/// ```
+impl Display for CommandLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys: Vec<String> = vec![];
+        let mut values: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, value) in self.iter_parsed() {
+            if !values.contains_key(&key) {
+                keys.push(key.to_owned());
             }
+            values.entry(key).or_default().extend(value);
         }
-        None
+
+        let options: Vec<String> = keys.iter()
+            .map(|key| match values[key].as_slice() {
+                [] => key.to_owned(),
+                values => format!("{}=[{}]", key, values.join(", ")),
+            })
+            .collect();
+        write!(f, "options: {{{}}}; args: [{}]", options.join(", "), self.get_args_without_program().join(", "))
     }
 }
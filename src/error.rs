@@ -1,5 +1,6 @@
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::io;
 
 use crate::option::{AnpOption, Required};
 
@@ -9,8 +10,9 @@ pub enum ParseErr {
     /// Missing required [`AnpOption`] or [`OptionGroup`].
     MissingOption(Vec<Required>),
 
-    /// Missing argument(s) passed to [`AnpOption`].
-    MissingArgument(AnpOption),
+    /// Missing argument(s) passed to [`AnpOption`], with the position of the token
+    /// where it's found if any.
+    MissingArgument(AnpOption, Option<TokenPosition>),
 
     /// Unknown error when processing options, possibly a bug.
     ProcessingErr {
@@ -23,46 +25,272 @@ pub enum ParseErr {
     AmbiguousOption {
         input_opt: String,
         matching_opts: Vec<String>,
+        position: Option<TokenPosition>,
     },
 
     /// Unrecognized option is passed to command line.
-    UnrecognizedOption(String),
+    ///
+    /// The `suggestion` is the closest long option if there is one similar enough.
+    UnrecognizedOption {
+        option: String,
+        suggestion: Option<String>,
+        position: Option<TokenPosition>,
+    },
+
+    /// When `allow_partial_matching` is disabled in [`DefaultParser`] and the option is
+    /// an abbreviation of the long option `expansion`, the error is raised.
+    AbbreviatedOption {
+        option: String,
+        expansion: String,
+    },
 
     /// The specified default values have a key that matches no [`AnpOption`].
     UndefinedDefaultOption {
         option: String,
         value: String,
     },
+
+    /// When `expand_at_files` is enabled in [`DefaultParser`] and the argument file
+    /// can't be read, or it references itself recursively, the error is raised.
+    ArgFileError {
+        path: String,
+        source: io::Error,
+    },
+
+    /// The argument at the index is not valid UTF-8, raised by [`crate::Parser::parse_os`]
+    /// and [`crate::Parser::parse_args_os`] unless the parser is lossy.
+    InvalidUtf8Argument(usize),
+
+    /// The value of an option can't be converted to the requested type, raised by
+    /// [`crate::CommandLine::get_parsed`]. The `reason` is the conversion error message.
+    ValueConversion {
+        option: String,
+        value: String,
+        type_name: String,
+        reason: String,
+    },
+
+    /// The path passed to an option with [`crate::ValueHint::FilePath`] or
+    /// [`crate::ValueHint::DirPath`] doesn't exist, see [`crate::ParserBuilder::set_validate_paths`].
+    InvalidPath {
+        option: String,
+        path: String,
+    },
+
+    /// An option taking a single value is passed more than once, see
+    /// [`crate::ParserBuilder::set_allow_duplicate_options`].
+    DuplicateOption(String),
+
+    /// The help option is passed, see [`crate::ParserBuilder::set_auto_help`].
+    HelpRequested,
+
+    /// The version option is passed, see [`crate::ParserBuilder::set_version`].
+    VersionRequested,
+}
+
+/// The position of a token in the parsed arguments, see [`ParseErr::position`].
+///
+/// The `index` counts the program name like [`crate::CommandLine::get_args_with_index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenPosition {
+    pub index: usize,
+    pub token: String,
+}
+
+impl Display for TokenPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "argument {} ('{}')", self.index, self.token)
+    }
+}
+
+/// The kind of [`ParseErr`], without the payload of the variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrKind {
+    MissingOption,
+    MissingArgument,
+    Unrecognized,
+    Ambiguous,
+    Processing,
+    UndefinedDefault,
+    ArgFile,
+    InvalidUtf8,
+    ValueConversion,
+    InvalidPath,
+    DuplicateOption,
+    HelpRequested,
+    VersionRequested,
+}
+
+impl ParseErr {
+    /// Get the kind of the error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, ParseErrKind, Parser};
+    ///
+    /// let err = DefaultParser::builder().build().parse_args(&Options::new(), &["-x"]).unwrap_err();
+    /// assert_eq!(ParseErrKind::Unrecognized, err.kind());
+    /// ```
+    pub fn kind(&self) -> ParseErrKind {
+        match self {
+            ParseErr::MissingOption(_) => ParseErrKind::MissingOption,
+            ParseErr::MissingArgument(..) => ParseErrKind::MissingArgument,
+            ParseErr::ProcessingErr { .. } => ParseErrKind::Processing,
+            ParseErr::AmbiguousOption { .. } => ParseErrKind::Ambiguous,
+            ParseErr::UnrecognizedOption { .. } => ParseErrKind::Unrecognized,
+            ParseErr::AbbreviatedOption { .. } => ParseErrKind::Unrecognized,
+            ParseErr::UndefinedDefaultOption { .. } => ParseErrKind::UndefinedDefault,
+            ParseErr::ArgFileError { .. } => ParseErrKind::ArgFile,
+            ParseErr::InvalidUtf8Argument(_) => ParseErrKind::InvalidUtf8,
+            ParseErr::ValueConversion { .. } => ParseErrKind::ValueConversion,
+            ParseErr::InvalidPath { .. } => ParseErrKind::InvalidPath,
+            ParseErr::DuplicateOption(_) => ParseErrKind::DuplicateOption,
+            ParseErr::HelpRequested => ParseErrKind::HelpRequested,
+            ParseErr::VersionRequested => ParseErrKind::VersionRequested,
+        }
+    }
+
+    /// Get the position of the token where the error is found, for
+    /// [`ParseErr::UnrecognizedOption`], [`ParseErr::AmbiguousOption`] and
+    /// [`ParseErr::MissingArgument`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option0("v", false, "print verbosely").unwrap();
+    ///
+    /// let err = DefaultParser::builder().build().parse_args(&options, &["-v", "a", "--xyz"]).unwrap_err();
+    /// assert_eq!(2, err.position().unwrap().index);
+    /// assert_eq!("parse error, unrecognized option '--xyz' near argument 2 ('--xyz')", err.to_string());
+    /// ```
+    pub fn position(&self) -> Option<&TokenPosition> {
+        match self {
+            ParseErr::MissingArgument(_, position)
+            | ParseErr::AmbiguousOption { position, .. }
+            | ParseErr::UnrecognizedOption { position, .. } => position.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Get the keys of the missing required options, excluding the option groups.
+    ///
+    /// Empty `Vec` is returned if the error is not [`ParseErr::MissingOption`].
+    pub fn missing_option_keys(&self) -> Vec<String> {
+        match self {
+            ParseErr::MissingOption(opt_list) => opt_list.iter()
+                .filter_map(|required| match required {
+                    Required::OptKey(key) => Some(key.to_owned()),
+                    Required::OptGroup(_) => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Get the missing required option groups, each group is the member keys sorted.
+    ///
+    /// Empty `Vec` is returned if the error is not [`ParseErr::MissingOption`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, OptionGroup, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_required_option("f", "file", true, "the input file").unwrap();
+    /// options.add_option_group(OptionGroup::new()
+    ///     .required(true)
+    ///     .add_option(AnpOption::builder().long_option("json").build().unwrap())
+    ///     .add_option(AnpOption::builder().long_option("yaml").build().unwrap()));
+    ///
+    /// let err = DefaultParser::builder().build().parse_args(&options, &[""; 0]).unwrap_err();
+    /// assert_eq!(vec!["f"], err.missing_option_keys());
+    /// assert_eq!(vec![vec!["json", "yaml"]], err.missing_groups());
+    /// ```
+    pub fn missing_groups(&self) -> Vec<Vec<String>> {
+        match self {
+            ParseErr::MissingOption(opt_list) => opt_list.iter()
+                .filter_map(|required| match required {
+                    Required::OptKey(_) => None,
+                    Required::OptGroup(group) => {
+                        let mut keys: Vec<String> = group.borrow().get_names().into_iter()
+                            .map(|k| k.to_owned())
+                            .collect();
+                        keys.sort();
+                        Some(keys)
+                    }
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
 }
 
-impl ParseErr {}
+/// The [`AnpOption`]s are compared by names, the positions are compared, and the [`io::Error`]s are compared by kind.
+impl PartialEq for ParseErr {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ParseErr::MissingOption(a), ParseErr::MissingOption(b)) => a == b,
+            (ParseErr::MissingArgument(a, a_position), ParseErr::MissingArgument(b, b_position)) =>
+                a == b && a_position == b_position,
+            (ParseErr::ProcessingErr { desc: a, source: a_source },
+                ParseErr::ProcessingErr { desc: b, source: b_source }) => a == b && a_source == b_source,
+            (ParseErr::AmbiguousOption { input_opt: a, matching_opts: a_opts, position: a_position },
+                ParseErr::AmbiguousOption { input_opt: b, matching_opts: b_opts, position: b_position }) =>
+                a == b && a_opts == b_opts && a_position == b_position,
+            (ParseErr::UnrecognizedOption { option: a, suggestion: a_suggestion, position: a_position },
+                ParseErr::UnrecognizedOption { option: b, suggestion: b_suggestion, position: b_position }) =>
+                a == b && a_suggestion == b_suggestion && a_position == b_position,
+            (ParseErr::AbbreviatedOption { option: a, expansion: a_expansion },
+                ParseErr::AbbreviatedOption { option: b, expansion: b_expansion }) => a == b && a_expansion == b_expansion,
+            (ParseErr::UndefinedDefaultOption { option: a, value: a_value },
+                ParseErr::UndefinedDefaultOption { option: b, value: b_value }) => a == b && a_value == b_value,
+            (ParseErr::ArgFileError { path: a, source: a_source },
+                ParseErr::ArgFileError { path: b, source: b_source }) => a == b && a_source.kind() == b_source.kind(),
+            (ParseErr::InvalidUtf8Argument(a), ParseErr::InvalidUtf8Argument(b)) => a == b,
+            (ParseErr::ValueConversion { option: a, value: a_value, type_name: a_type, reason: a_reason },
+                ParseErr::ValueConversion { option: b, value: b_value, type_name: b_type, reason: b_reason }) =>
+                a == b && a_value == b_value && a_type == b_type && a_reason == b_reason,
+            (ParseErr::InvalidPath { option: a, path: a_path },
+                ParseErr::InvalidPath { option: b, path: b_path }) => a == b && a_path == b_path,
+            (ParseErr::DuplicateOption(a), ParseErr::DuplicateOption(b)) => a == b,
+            (ParseErr::HelpRequested, ParseErr::HelpRequested) => true,
+            (ParseErr::VersionRequested, ParseErr::VersionRequested) => true,
+            _ => false,
+        }
+    }
+}
 
 impl Display for ParseErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut msg = String::new();
         match self {
             ParseErr::MissingOption(opt_list) => {
-                msg.push_str("missing option '");
-                msg.push_str(&match opt_list.first().unwrap() {
-                    Required::OptKey(key) => key.to_string(),
-                    Required::OptGroup(group) => {
-                        group.borrow().get_options().iter()
-                            .map(|opt| opt.borrow())
-                            .fold(String::new(), |mut a, b| {
-                                if !a.is_empty() {
-                                    a.push_str(" | ");
-                                }
-                                a.push_str(b.get_key());
-                                return a;
-                            })
-                    }
-                });
-                msg.push_str("'");
+                let missing: Vec<String> = opt_list.iter()
+                    .map(|required| match required {
+                        Required::OptKey(key) => format!("'{}'", key),
+                        Required::OptGroup(group) => format!("one of {}", group.borrow().member_names()),
+                    })
+                    .collect();
+                if missing.len() == 1 {
+                    msg.push_str("missing option ");
+                } else {
+                    msg.push_str("missing options: ");
+                }
+                msg.push_str(&missing.join(", "));
             }
-            ParseErr::MissingArgument(option) => {
+            ParseErr::MissingArgument(option, position) => {
                 msg.push_str("missing argument for option '");
                 msg.push_str(option.get_key());
                 msg.push_str("'");
+                if let Some(position) = position {
+                    msg.push_str(&format!(" near {}", position));
+                }
             }
             ParseErr::ProcessingErr { desc, source } => {
                 if let Some(err) = source {
@@ -71,22 +299,56 @@ impl Display for ParseErr {
                     msg.push_str(desc);
                 }
             }
-            ParseErr::AmbiguousOption { input_opt, matching_opts } => {
+            ParseErr::AmbiguousOption { input_opt, matching_opts, position } => {
                 msg.push_str("ambiguous option '");
                 msg.push_str(input_opt);
-                msg.push_str("', possible options are ");
+                msg.push('\'');
+                if let Some(position) = position {
+                    msg.push_str(&format!(" near {}", position));
+                }
+                msg.push_str(", possible options are ");
                 msg.push_str(&matching_opts.join(", "));
             }
-            ParseErr::UnrecognizedOption(opt) => {
+            ParseErr::UnrecognizedOption { option, suggestion, position } => {
                 msg.push_str("unrecognized option '");
-                msg.push_str(opt);
+                msg.push_str(option);
                 msg.push_str("'");
+                if let Some(position) = position {
+                    msg.push_str(&format!(" near {}", position));
+                }
+                if let Some(suggestion) = suggestion {
+                    msg.push_str("; did you mean '");
+                    msg.push_str(suggestion);
+                    msg.push_str("'?");
+                }
+            }
+            ParseErr::AbbreviatedOption { option, expansion } => {
+                msg.push_str(&format!("unrecognized option '{}'; abbreviations are disabled, did you mean '{}'?",
+                                      option, expansion));
             }
             ParseErr::UndefinedDefaultOption { option, .. } => {
                 msg.push_str("undefined default option '");
                 msg.push_str(option);
                 msg.push_str("'");
             }
+            ParseErr::ArgFileError { path, source } => {
+                msg.push_str(&format!("unable to read argument file '{}': {}", path, source));
+            }
+            ParseErr::InvalidUtf8Argument(index) => {
+                msg.push_str(&format!("argument at index {} is not valid UTF-8", index));
+            }
+            ParseErr::ValueConversion { option, value, type_name, reason } => {
+                msg.push_str(&format!("unable to parse option '{}', expect type '{}', got '{}': {}",
+                                      option, type_name, value, reason));
+            }
+            ParseErr::InvalidPath { option, path } => {
+                msg.push_str(&format!("path '{}' of option '{}' doesn't exist", path, option));
+            }
+            ParseErr::DuplicateOption(option) => {
+                msg.push_str(&format!("option '{}' is passed more than once", option));
+            }
+            ParseErr::HelpRequested => msg.push_str("help requested"),
+            ParseErr::VersionRequested => msg.push_str("version requested"),
         };
         write!(f, "parse error, {}", &msg)
     }
@@ -97,11 +359,14 @@ impl Error for ParseErr {
         if let Self::ProcessingErr { source, .. } = &self {
             return source.as_ref().map(|s| s as &dyn Error);
         }
+        if let Self::ArgFileError { source, .. } = &self {
+            return Some(source);
+        }
         return None;
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct OptionErr {
     option: Option<AnpOption>,
     description: String,
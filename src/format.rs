@@ -1,9 +1,12 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::io;
 use std::io::{BufRead, Cursor, Write};
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::option::{AnpOption, OptionGroup, Options};
+use crate::option::{AnpOption, ArgCount, OptionGroup, Options, ValueHint};
+use crate::util::Util;
 
 pub const DEFAULT_LINE_SEPARATOR: &str = if cfg!(windows) { "\r\n" } else { "\n" };
 
@@ -15,17 +18,29 @@ const DEFAULT_OPT_PREFIX: &str = "-";
 const DEFAULT_LONG_OPT_PREFIX: &str = "--";
 const DEFAULT_ARG_NAME: &str = "arg";
 
+/// How to wrap a word longer than the width available, like a long URL in the description.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LongWordPolicy {
+    /// Break the word at the width, continuing on the next line.
+    Break,
+    /// Keep the word on a line, exceeding the width.
+    Overflow,
+    /// Cut the word to fit and append the suffix, like `...`.
+    Truncate(String),
+}
+
 /// `HelpFormatter` helps print usage information for the [`Options`].
 ///
 /// The output format is like:
 /// ```txt
-/// usage: <cmd_syntax> [opt_usage]
+/// usage: [program_name] <cmd_syntax> [opt_usage]
 /// [header]
 ///     -<opt>, --<long_opt>               <description>
 ///     -<opt>                             <description>
 ///     --<long_opt>                       <description>
 ///     -<opt>, --<long_opt>=[opt_name]    <description>
 /// [footer]
+/// [examples]
 /// ```
 pub struct HelpFormatter {
     width: usize,
@@ -36,14 +51,152 @@ pub struct HelpFormatter {
     opt_prefix: String,
     long_opt_prefix: String,
     arg_name: String,
+    default_arg_name: Option<String>,
+    arg_name_prefix: String,
+    arg_name_suffix: String,
+    multi_arg_separator: Option<String>,
     option_comparator: Option<Box<dyn Fn(&AnpOption, &AnpOption) -> Ordering>>,
     cmd_syntax: String,
+    program_name: Option<String>,
     auto_usage: bool,
+    show_defaults: bool,
+    required_marker: String,
+    max_opt_width: Option<usize>,
+    min_opt_width: Option<usize>,
+    examples: Vec<(String, String)>,
+    align_long_opts: bool,
+    long_word_policy: LongWordPolicy,
     header: Option<String>,
     footer: Option<String>,
 }
 
+/// A builder struct to create [`HelpFormatter`].
+///
+/// # Example
+///
+/// ```
+/// use anpcli::HelpFormatter;
+///
+/// let formatter = HelpFormatter::builder("ls")
+///     .set_width(60)
+///     .set_header("List directory contents.")
+///     .set_auto_usage(true)
+///     .build();
+/// assert_eq!(60, formatter.get_width());
+/// ```
+pub struct HelpFormatterBuilder {
+    formatter: HelpFormatter,
+}
+
+impl HelpFormatterBuilder {
+    pub fn build(self) -> HelpFormatter {
+        self.formatter
+    }
+
+    /// See [`HelpFormatter::set_width`].
+    pub fn set_width(mut self, width: usize) -> Self {
+        self.formatter.set_width(width);
+        self
+    }
+
+    /// See [`HelpFormatter::set_left_padding`].
+    pub fn set_left_padding(mut self, padding: usize) -> Self {
+        self.formatter.set_left_padding(padding);
+        self
+    }
+
+    /// See [`HelpFormatter::set_desc_padding`].
+    pub fn set_desc_padding(mut self, padding: usize) -> Self {
+        self.formatter.set_desc_padding(padding);
+        self
+    }
+
+    /// See [`HelpFormatter::set_syntax_prefix`].
+    pub fn set_syntax_prefix(mut self, prefix: &str) -> Self {
+        self.formatter.set_syntax_prefix(prefix);
+        self
+    }
+
+    /// Set the prefix of short options, which defaults to `-`.
+    pub fn set_opt_prefix(mut self, prefix: &str) -> Self {
+        self.formatter.opt_prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the prefix of long options, which defaults to `--`.
+    pub fn set_long_opt_prefix(mut self, prefix: &str) -> Self {
+        self.formatter.long_opt_prefix = prefix.to_string();
+        self
+    }
+
+    /// See [`HelpFormatter::set_arg_name`].
+    pub fn set_arg_name(mut self, arg_name: &str) -> Self {
+        self.formatter.set_arg_name(arg_name);
+        self
+    }
+
+    /// See [`HelpFormatter::set_default_arg_name`].
+    pub fn set_default_arg_name(mut self, arg_name: &str) -> Self {
+        self.formatter.set_default_arg_name(arg_name);
+        self
+    }
+
+    /// See [`HelpFormatter::set_multi_arg_separator`].
+    pub fn set_multi_arg_separator(mut self, separator: &str) -> Self {
+        self.formatter.set_multi_arg_separator(separator);
+        self
+    }
+
+    /// See [`HelpFormatter::set_newline`].
+    pub fn set_newline(mut self, newline: &str) -> Self {
+        self.formatter.set_newline(newline);
+        self
+    }
+
+    /// See [`HelpFormatter::set_program_name`].
+    pub fn set_program_name(mut self, name: &str) -> Self {
+        self.formatter.set_program_name(name);
+        self
+    }
+
+    /// See [`HelpFormatter::set_header`].
+    pub fn set_header(mut self, header: &str) -> Self {
+        self.formatter.set_header(header);
+        self
+    }
+
+    /// See [`HelpFormatter::set_footer`].
+    pub fn set_footer(mut self, footer: &str) -> Self {
+        self.formatter.set_footer(footer);
+        self
+    }
+
+    /// See [`HelpFormatter::set_auto_usage`].
+    pub fn set_auto_usage(mut self, auto_usage: bool) -> Self {
+        self.formatter.set_auto_usage(auto_usage);
+        self
+    }
+
+    /// See [`HelpFormatter::set_long_word_policy`].
+    pub fn set_long_word_policy(mut self, policy: LongWordPolicy) -> Self {
+        self.formatter.set_long_word_policy(policy);
+        self
+    }
+
+    /// See [`HelpFormatter::set_opt_comparator`].
+    pub fn set_opt_comparator(mut self, comparator: Option<Box<dyn Fn(&AnpOption, &AnpOption) -> Ordering>>) -> Self {
+        self.formatter.set_opt_comparator(comparator);
+        self
+    }
+}
+
 impl HelpFormatter {
+    /// Get the builder to config formatter, which starts from the default configuration
+    /// of [`Self::new`].
+    pub fn builder(cmd_syntax: &str) -> HelpFormatterBuilder {
+        HelpFormatterBuilder { formatter: HelpFormatter::new(cmd_syntax) }
+    }
+
     /// Create a `HelpFormatter` with default configuration.
     ///
     /// The `cmd_syntax` is typically the name of the executable with positional options.
@@ -58,9 +211,21 @@ impl HelpFormatter {
             opt_prefix: DEFAULT_OPT_PREFIX.to_string(),
             long_opt_prefix: DEFAULT_LONG_OPT_PREFIX.to_string(),
             arg_name: DEFAULT_ARG_NAME.to_string(),
+            default_arg_name: None,
+            arg_name_prefix: "<".to_string(),
+            arg_name_suffix: ">".to_string(),
+            multi_arg_separator: None,
             option_comparator: Some(Box::new(|x, y| x.get_key().cmp(y.get_key()))),
             cmd_syntax: cmd_syntax.to_string(),
+            program_name: None,
             auto_usage: false,
+            show_defaults: false,
+            required_marker: String::new(),
+            max_opt_width: None,
+            min_opt_width: None,
+            examples: vec![],
+            align_long_opts: false,
+            long_word_policy: LongWordPolicy::Break,
             header: None,
             footer: None,
         }
@@ -75,15 +240,13 @@ impl HelpFormatter {
             buff.push_str("-");
             buff.push_str(opt);
         } else {
-            buff.push_str("--");
+            buff.push_str(if option.is_single_hyphen_long() { "-" } else { "--" });
             buff.push_str(option.get_long_opt().unwrap());
         }
 
-        if option.has_arg() && (option.get_arg_name().is_none() || !option.get_arg_name().unwrap().is_empty()) {
-            buff.push_str(" ");
-            buff.push_str("<");
-            buff.push_str(if option.get_arg_name().is_some() { option.get_arg_name().unwrap() } else { self.get_arg_name() });
-            buff.push_str(">");
+        if let Some(placeholder) = self.arg_placeholder(option) {
+            buff.push(' ');
+            buff.push_str(&placeholder);
         }
 
         if !required {
@@ -91,10 +254,41 @@ impl HelpFormatter {
         }
     }
 
-    fn append_option_group(&self, buff: &mut String, group: &OptionGroup) {
-        if !group.is_required() {
-            buff.push_str("[")
+    /// The argument placeholder like `<arg>` of the option if it takes an argument,
+    /// repeated for options taking a fixed number of arguments like `<arg>,<arg>` if
+    /// the multi-arg separator is set.
+    ///
+    /// Property options and options with a value separator show a single placeholder,
+    /// as their arguments are passed in one token.
+    ///
+    /// An empty argument name means the option takes an argument without a placeholder.
+    fn arg_placeholder(&self, option: &AnpOption) -> Option<String> {
+        let arg_name = option.get_arg_name().map_or(self.get_arg_name(), |a| a);
+        if option.has_arg() && !arg_name.is_empty() {
+            let placeholder = format!("{}{}{}", self.arg_name_prefix, arg_name, self.arg_name_suffix);
+            match (option.get_args(), &self.multi_arg_separator) {
+                (ArgCount::Fixed(n), Some(separator))
+                if *n > 1 && !option.is_property() && !option.has_value_separator() => {
+                    Some(vec![placeholder; *n].join(separator))
+                }
+                _ => Some(placeholder),
+            }
+        } else {
+            None
         }
+    }
+
+    fn long_opt_prefix_of(&self, option: &AnpOption) -> &str {
+        if option.is_single_hyphen_long() {
+            self.get_opt_prefix()
+        } else {
+            self.get_long_opt_prefix()
+        }
+    }
+
+    /// Append the group like `[-a | -b]`, or `(-a | -b)` if the group is required.
+    fn append_option_group(&self, buff: &mut String, group: &OptionGroup) {
+        buff.push(if group.is_required() { '(' } else { '[' });
 
         let mut options = group.get_options();
         if let Some(comparator) = self.get_option_comparator() {
@@ -110,9 +304,7 @@ impl HelpFormatter {
             }
         }
 
-        if !group.is_required() {
-            buff.push_str("]")
-        }
+        buff.push(if group.is_required() { ')' } else { ']' });
     }
 
     /// Retrieve the option comparator, which is used to sort the [`AnpOption`]
@@ -125,44 +317,78 @@ impl HelpFormatter {
         " ".repeat(len)
     }
 
+    /// Find the byte position to wrap `text` after `start_pos`, the `width` is counted in chars.
     fn find_wrap_pos(&self, text: &str, width: usize, start_pos: usize) -> Option<usize> {
         let trunc_text = &text[start_pos..];
+        let chars: Vec<(usize, char)> = trunc_text.char_indices().take(width + 1).collect();
 
-        let pos = trunc_text.find('\n');
-        if pos.is_some() && pos.as_ref().unwrap() <= &width {
-            return Some(pos.unwrap() + start_pos + 1);
+        if let Some((pos, c)) = chars.iter().find(|(_, c)| *c == '\n') {
+            return Some(start_pos + pos + c.len_utf8());
         }
 
-        let pos = trunc_text.find('\t');
-        if pos.is_some() && pos.as_ref().unwrap() <= &width {
-            return Some(pos.unwrap() + start_pos + 1);
+        if let Some((pos, c)) = chars.iter().find(|(_, c)| *c == '\t') {
+            return Some(start_pos + pos + c.len_utf8());
         }
 
-        if start_pos + width >= text.len() {
+        if chars.len() <= width {
             return None;
         }
 
-        let mut pos = None;
-        for i in (start_pos..start_pos + width + 1).rev() {
-            let c = *text.as_bytes().get(i).unwrap() as char;
-            if c == ' ' || c == '\r' || c == '\n' {
-                pos = Some(i);
-                break;
-            }
+        let pos = chars.iter().rev()
+            .find(|(_, c)| *c == ' ' || *c == '\r' || *c == '\n')
+            .map(|(pos, _)| *pos);
+        if let Some(pos) = pos.filter(|pos| *pos > 0) {
+            return Some(start_pos + pos);
         }
 
-        if pos.is_some() && pos.as_ref().unwrap() > &start_pos {
-            return pos;
+        // no space to wrap at, the word is longer than `width`
+        self.find_long_word_break(trunc_text, chars[width].0, width).map(|pos| start_pos + pos)
+    }
+
+    /// Find the byte position to wrap the long word of `text` starting before byte `from`,
+    /// according to the [`LongWordPolicy`].
+    fn find_long_word_break(&self, text: &str, from: usize, width: usize) -> Option<usize> {
+        match self.long_word_policy {
+            LongWordPolicy::Overflow => text[from..].find(char::is_whitespace).map(|pos| from + pos),
+            _ => text.char_indices().nth(width).map(|(pos, _)| pos),
         }
+    }
+
+    /// Cut the words of `text` longer than `max` chars to fit if the [`LongWordPolicy`]
+    /// is [`LongWordPolicy::Truncate`].
+    fn apply_long_word_policy<'a>(&self, text: &'a str, max: usize) -> Cow<'a, str> {
+        match &self.long_word_policy {
+            LongWordPolicy::Truncate(suffix) => Cow::Owned(self.truncate_long_words(text, max, suffix)),
+            _ => Cow::Borrowed(text),
+        }
+    }
 
-        let mut len = width;
-        while len > 0 {
-            if text.is_char_boundary(start_pos + len) {
-                return Some(start_pos + width);
+    /// Cut the words longer than `max` chars to fit, ending with `suffix`.
+    ///
+    /// A word is kept as is if `max` leaves no room for any char before the suffix.
+    fn truncate_long_words(&self, text: &str, max: usize, suffix: &str) -> String {
+        let keep = max.saturating_sub(suffix.chars().count());
+        let mut buff = String::new();
+        let mut word = String::new();
+        let flush = |buff: &mut String, word: &mut String| {
+            if keep > 0 && word.chars().count() > max {
+                buff.extend(word.chars().take(keep));
+                buff.push_str(suffix);
+            } else {
+                buff.push_str(word);
+            }
+            word.clear();
+        };
+        for c in text.chars() {
+            if c.is_whitespace() {
+                flush(&mut buff, &mut word);
+                buff.push(c);
+            } else {
+                word.push(c);
             }
-            len -= 1;
         }
-        panic!("should not happen");
+        flush(&mut buff, &mut word);
+        buff
     }
 
     /// Get the argument name displayed in usage.
@@ -170,6 +396,11 @@ impl HelpFormatter {
         &self.arg_name
     }
 
+    /// Get the separator between the argument placeholders of options taking multiple arguments.
+    pub fn get_multi_arg_separator(&self) -> Option<&str> {
+        self.multi_arg_separator.as_deref()
+    }
+
     /// Get number of padding space for option description.
     pub fn get_desc_padding(&self) -> usize {
         self.desc_pad
@@ -207,11 +438,45 @@ impl HelpFormatter {
         self.width
     }
 
-    /// Set the argument name displayed in option usage.
+    /// Set the argument name displayed in option usage, which is the default for options
+    /// without their own argument name.
+    ///
+    /// The generated zsh completion prefers a name from the [`ValueHint`] or the allowed
+    /// values of the option, like `file` or `value`, and falls back to this name unless
+    /// [`Self::set_default_arg_name`] is set.
     pub fn set_arg_name(&mut self, arg_name: &str) {
         self.arg_name = arg_name.to_string();
     }
 
+    /// Get the fallback argument name of the generated completion, see [`Self::set_default_arg_name`].
+    pub fn get_default_arg_name(&self) -> Option<&str> {
+        self.default_arg_name.as_deref()
+    }
+
+    /// Set the argument name of the generated completion for options without their own
+    /// argument name, [`ValueHint`] or allowed values. Unlike [`Self::set_arg_name`],
+    /// the help listing and usage are not changed.
+    pub fn set_default_arg_name(&mut self, arg_name: &str) {
+        self.default_arg_name = Some(arg_name.to_string());
+    }
+
+    /// Set the brackets around the argument name, which default to `<` and `>`.
+    ///
+    /// For example, `("[", "]")` shows `-f [arg]`, and `("", "")` shows `-f arg`.
+    pub fn set_arg_name_format(&mut self, prefix: &str, suffix: &str) {
+        self.arg_name_prefix = prefix.to_string();
+        self.arg_name_suffix = suffix.to_string();
+    }
+
+    /// Set the separator between the argument placeholders of options taking a fixed
+    /// number of arguments. By default, a single placeholder is shown.
+    ///
+    /// For example, `","` shows `--color <R>,<R>,<R>` for an option with 3 arguments.
+    /// Property options and options with a value separator still show one placeholder.
+    pub fn set_multi_arg_separator(&mut self, separator: &str) {
+        self.multi_arg_separator = Some(separator.to_string());
+    }
+
     /// Set number of padding space for option description.
     pub fn set_desc_padding(&mut self, padding: usize) {
         self.desc_pad = padding;
@@ -229,6 +494,9 @@ impl HelpFormatter {
 
     /// Set the option comparator, which is used to sort the [`AnpOption`]
     /// when printing options.
+    ///
+    /// If `comparator` is [`None`], options are printed in the order they were
+    /// added to [`Options`].
     pub fn set_opt_comparator(&mut self, comparator: Option<Box<dyn Fn(&AnpOption, &AnpOption) -> Ordering>>) {
         self.option_comparator = comparator;
     }
@@ -251,6 +519,22 @@ impl HelpFormatter {
         self.cmd_syntax = syntax.to_string();
     }
 
+    /// Set the program name displayed before the cmd syntax, so the cmd syntax can
+    /// describe the positional arguments only.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{HelpFormatter, Options};
+    ///
+    /// let mut formatter = HelpFormatter::new("[<path>]");
+    /// formatter.set_program_name("cd");
+    /// assert_eq!("usage: cd [<path>]", formatter.usage_to_string(&Options::new()));
+    /// ```
+    pub fn set_program_name(&mut self, name: &str) {
+        self.program_name = Some(name.to_string());
+    }
+
     /// Set header message.
     pub fn set_header(&mut self, header: &str) {
         self.header = Some(header.to_string());
@@ -261,179 +545,470 @@ impl HelpFormatter {
         self.footer = Some(footer.to_string());
     }
 
+    /// Add an example invocation with its description, which is listed under the
+    /// `Examples:` heading after the footer in [`Self::print_help`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{HelpFormatter, Options};
+    ///
+    /// let mut formatter = HelpFormatter::new("ls");
+    /// formatter.add_example("ls -l /tmp", "list the files in /tmp in long format");
+    /// assert_eq!("usage: ls\n\n\nExamples:\n    ls -l /tmp\n        list the files in /tmp in long format\n",
+    ///            formatter.help_to_string(&Options::new()));
+    /// ```
+    pub fn add_example(&mut self, invocation: &str, description: &str) {
+        self.examples.push((invocation.to_string(), description.to_string()));
+    }
+
     /// Set if auto print the option usage after `cmd_syntax`.
     pub fn set_auto_usage(&mut self, auto_usage: bool) {
         self.auto_usage = auto_usage;
     }
 
+    /// Set if append the default value to the option description, like `(default: 4)`.
+    ///
+    /// The default value is either from [`Options::set_defaults`] or from
+    /// [`crate::OptionBuilder::default_value`].
+    pub fn set_show_defaults(&mut self, show_defaults: bool) {
+        self.show_defaults = show_defaults;
+    }
+
+    /// Set the marker appended to the required options in the option listing, like `*`.
+    ///
+    /// It defaults to empty, which means required options are not marked. If any required
+    /// option is listed, a note explaining the marker is printed after the options.
+    pub fn set_required_marker(&mut self, marker: &str) {
+        self.required_marker = marker.to_string();
+    }
+
+    /// Set if align the long options in the option listing.
+    ///
+    /// If set to `true`, the options with only a long option name are indented as if they
+    /// had a short option name, so all long option names line up vertically:
+    ///
+    /// ```txt
+    ///     -v, --verbose
+    ///         --color
+    /// ```
+    pub fn set_align_long_opts(&mut self, align_long_opts: bool) {
+        self.align_long_opts = align_long_opts;
+    }
+
+    /// Set how to wrap a word longer than the width available, which is
+    /// [`LongWordPolicy::Break`] by default.
+    pub fn set_long_word_policy(&mut self, policy: LongWordPolicy) {
+        self.long_word_policy = policy;
+    }
+
+    /// Get how to wrap a word longer than the width available.
+    pub fn get_long_word_policy(&self) -> &LongWordPolicy {
+        &self.long_word_policy
+    }
+
+    /// Set the maximum width of the option name column in the option listing.
+    ///
+    /// By default, the column is as wide as the longest option name. If set, the
+    /// description of an option whose name is wider than `width` starts on the next line,
+    /// so a single long option doesn't push all descriptions to the right.
+    pub fn set_max_opt_width(&mut self, width: usize) {
+        self.max_opt_width = Some(width);
+    }
+
+    /// Set the minimum width of the option name column in the option listing.
+    ///
+    /// By default, the column is as wide as the longest option name. If set, the column
+    /// is at least `width` wide, which takes precedence over [`Self::set_max_opt_width`].
+    pub fn set_min_opt_width(&mut self, width: usize) {
+        self.min_opt_width = Some(width);
+    }
+
+    /// Get the marker appended to the required options.
+    pub fn get_required_marker(&self) -> &str {
+        &self.required_marker
+    }
+
     /// Print help message of the [`Options`] to the `out` sinks.
     ///
+    /// # Error
+    ///
+    /// The error of writing to `out` is returned, e.g. when the pipe is closed by the reader.
+    ///
     /// # Example
     ///
     /// ```
     /// use std::io::{stderr};
     /// use anpcli::{HelpFormatter, Options};
-    /// HelpFormatter::new("ls").print_help(&mut stderr(), &Options::new());
+    /// HelpFormatter::new("ls").print_help(&mut stderr(), &Options::new()).unwrap();
     /// ```
-    pub fn print_help<T: Write>(&self, out: &mut T, options: &Options) {
-        if self.auto_usage {
-            self.print_usage_with_options(out, options);
-        } else {
-            self.print_usage(out);
+    pub fn print_help<T: Write>(&self, out: &mut T, options: &Options) -> io::Result<()> {
+        write!(out, "{}", self.help_to_string(options))
+    }
+
+    /// Print detailed information for options only.
+    ///
+    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
+    pub fn print_options<T: Write>(&self, out: &mut T, options: &Options) -> io::Result<()> {
+        write!(out, "{}", self.options_to_string(options))
+    }
+
+    /// Write detailed information for options like [`Self::print_options`], writing each
+    /// option entry to `out` once it's rendered instead of building the whole listing
+    /// in memory, for a huge number of options.
+    ///
+    /// The options are still iterated twice to compute the width of the option column.
+    pub fn write_options_streaming<T: Write>(&self, out: &mut T, options: &Options) -> io::Result<()> {
+        self.render_options_with(options, |text| out.write_all(text.as_bytes()))
+    }
+
+    /// Print cmd syntax without option usage.
+    ///
+    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
+    pub fn print_usage<T: Write>(&self, out: &mut T) -> io::Result<()> {
+        let mut buff = String::new();
+        self.render_usage(&mut buff, None);
+        write!(out, "{}", buff)
+    }
+
+    /// Print cmd syntax with option usage.
+    ///
+    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
+    pub fn print_usage_with_options<T: Write>(&self, out: &mut T, options: &Options) -> io::Result<()> {
+        let mut buff = String::new();
+        self.render_usage(&mut buff, Some(options));
+        write!(out, "{}", buff)
+    }
+
+    /// Get the help message of the [`Options`] as a `String`, which is the same as
+    /// the output of [`Self::print_help`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{HelpFormatter, Options};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("v", "verbose", false, "print more messages").unwrap();
+    /// let help = HelpFormatter::new("ls").help_to_string(&options);
+    /// assert_eq!("usage: ls\n    -v, --verbose    print more messages\n", help);
+    /// ```
+    pub fn help_to_string(&self, options: &Options) -> String {
+        let mut buff = String::new();
+        buff.push_str(&self.usage_to_string(options));
+        buff.push_str(self.get_newline());
+
+        if let Some(header) = self.header.as_ref().filter(|h| !h.is_empty()) {
+            self.render_wrapped_text_block(&mut buff, 0, header);
+            buff.push_str(self.get_newline());
         }
 
-        write!(out, "{}", self.get_newline()).unwrap();
+        self.render_options(&mut buff, options);
+
+        if let Some(footer) = self.footer.as_ref().filter(|f| !f.is_empty()) {
+            buff.push_str(self.get_newline());
+            self.render_wrapped_text_block(&mut buff, 0, footer);
+        }
 
-        if self.header.as_ref().is_some_and(|h| !h.is_empty()) {
-            self.print_wrapped(out, self.header.as_ref().unwrap());
-            write!(out, "{}", self.get_newline()).unwrap();
+        if !self.examples.is_empty() {
+            buff.push_str(self.get_newline());
+            buff.push_str(self.get_newline());
+            self.render_examples(&mut buff);
         }
 
-        self.print_options(out, options);
+        buff.push_str(self.get_newline());
+        buff
+    }
 
-        if self.footer.as_ref().is_some_and(|f| !f.is_empty()) {
-            write!(out, "{}", self.get_newline()).unwrap();
-            self.print_wrapped(out, self.footer.as_ref().unwrap());
+    fn render_examples(&self, buff: &mut String) {
+        buff.push_str("Examples:");
+        for (invocation, description) in self.examples.iter() {
+            buff.push_str(self.get_newline());
+            buff.push_str(&self.wrap_text(invocation, self.get_left_padding()));
+            if !description.is_empty() {
+                buff.push_str(self.get_newline());
+                buff.push_str(&self.wrap_text(description, self.get_left_padding() * 2));
+            }
         }
+    }
 
-        write!(out, "{}", self.get_newline()).unwrap();
+    /// Get the usage line as a `String`, the option usage is included if auto usage
+    /// is enabled, see [`Self::set_auto_usage`].
+    pub fn usage_to_string(&self, options: &Options) -> String {
+        let mut buff = String::new();
+        self.render_usage(&mut buff, Some(options).filter(|_| self.auto_usage));
+        buff
     }
 
-    /// Print detailed information for options only.
-    ///
-    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
-    pub fn print_options<T: Write>(&self, out: &mut T, options: &Options) {
+    /// Get the detailed information for options as a `String`.
+    pub fn options_to_string(&self, options: &Options) -> String {
         let mut buff = String::new();
         self.render_options(&mut buff, options);
-        write!(out, "{}", buff).unwrap();
+        buff
     }
 
-    /// Print cmd syntax without option usage.
+    /// The program name followed by the cmd syntax, skipping the empty ones.
+    fn full_cmd_syntax(&self) -> String {
+        let mut syntax = self.program_name.clone().unwrap_or_default();
+        if !syntax.is_empty() && !self.cmd_syntax.is_empty() {
+            syntax.push(' ');
+        }
+        syntax.push_str(&self.cmd_syntax);
+        syntax
+    }
+
+    /// Render the wrapped cmd syntax, with the option usage if `options` is given.
     ///
-    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
-    pub fn print_usage<T: Write>(&self, out: &mut T) {
-        let arg_pos = self.cmd_syntax.find(' ').map(|x| x + 1).unwrap_or(0);
+    /// The trailing spaces of the syntax prefix are dropped if the cmd syntax is empty.
+    fn render_usage(&self, buff: &mut String, options: Option<&Options>) {
+        let cmd_syntax = self.full_cmd_syntax();
+        let prefix = if cmd_syntax.is_empty() { self.get_syntax_prefix().trim_end() } else { self.get_syntax_prefix() };
+        if let Some(options) = options {
+            let syntax = format!("{}{}", prefix, cmd_syntax);
+            buff.push_str(&syntax);
+
+            // an option or a group is never split, the continuation lines are aligned
+            // under the first option
+            let mut tab = syntax.chars().count() + 1;
+            if tab >= self.get_width() {
+                tab = 1;
+            }
+            let mut line_len = syntax.chars().count();
+            for token in self.usage_tokens(options) {
+                let token_len = token.chars().count();
+                if line_len + 1 + token_len > self.get_width() && line_len > tab {
+                    buff.push_str(self.get_newline());
+                    buff.push_str(&self.create_padding(tab));
+                    line_len = tab;
+                } else {
+                    buff.push(' ');
+                    line_len += 1;
+                }
+                buff.push_str(&token);
+                line_len += token_len;
+            }
+        } else {
+            let arg_pos = cmd_syntax.find(' ').map(|x| x + 1).unwrap_or(0);
 
-        self.print_wrapped_with_tab(
-            out, &format!("{}{}", self.get_syntax_prefix(), self.cmd_syntax),
-            self.get_syntax_prefix().len() + arg_pos);
+            self.render_wrapped_text_block(buff, prefix.len() + arg_pos, &format!("{}{}", prefix, cmd_syntax));
+        }
     }
 
-    /// Print cmd syntax with option usage.
+    /// The options and option groups in the usage, like `[-x <arg>]` or `(-a | -b)`.
     ///
-    /// Also see [`HelpFormatter`],  [`HelpFormatter::print_help`].
-    pub fn print_usage_with_options<T: Write>(&self, out: &mut T, options: &Options) {
-        let mut buff = format!("{}{} ", self.get_syntax_prefix(), self.cmd_syntax);
-
+    /// Each word of a group note is a token, so the note wraps like the options.
+    fn usage_tokens(&self, options: &Options) -> Vec<String> {
+        let mut tokens = vec![];
         let mut processed_groups = vec![];
 
-        let mut opt_list = options.get_options();
+        let mut opt_list = options.get_options_in_order();
         if self.get_option_comparator().is_some() {
             let cmp = self.get_option_comparator().unwrap();
             opt_list.sort_by(|x, y| cmp(&x, &y));
         }
 
-        let len = opt_list.len();
-        for (i, opt) in opt_list.into_iter().enumerate() {
+        for opt in opt_list.into_iter() {
+            let mut token = String::new();
             let group = options.get_option_group(&opt);
             if let Some(group) = group {
                 if !processed_groups.contains(&group) {
                     processed_groups.push(Rc::clone(&group));
 
-                    self.append_option_group(&mut buff, &group.borrow())
+                    self.append_option_group(&mut token, &group.borrow());
+                    tokens.push(token);
+                    if let Some(note) = group.borrow().get_note() {
+                        tokens.extend(note.split_whitespace().map(|word| word.to_owned()));
+                    }
                 }
             } else {
-                self.append_option(&mut buff, &opt, opt.is_required());
+                self.append_option(&mut token, &opt, opt.is_required());
+                tokens.push(token);
             }
+        }
+        tokens
+    }
 
-            if i != len - 1 {
-                buff.push_str(" ");
+    /// Get the sections of the options, options without section come first,
+    /// then sections in declaration order.
+    fn get_sections(&self, options: &Options) -> Vec<Option<String>> {
+        let mut sections: Vec<Option<String>> = vec![None];
+        for option in options.get_options_in_order() {
+            let section = option.get_section().map(|s| s.to_owned());
+            if !sections.contains(&section) {
+                sections.push(section);
             }
         }
-
-        let tab = buff.find(' ').map(|x| x + 1).unwrap_or(0);
-        self.print_wrapped_with_tab(out, &buff, tab);
+        sections
     }
 
-    fn print_wrapped<T: Write>(&self, out: &mut T, text: &str) {
-        self.print_wrapped_with_tab(out, text, 0);
+    fn render_options(&self, buff: &mut String, options: &Options) {
+        // writing to a `String` never fails
+        let _ = self.render_options_with(options, |text| {
+            buff.push_str(text);
+            Ok(())
+        });
     }
 
-    fn print_wrapped_with_tab<T: Write>(&self, out: &mut T, text: &str, next_line_tap_stop: usize) {
-        let mut buff = String::new();
-        self.render_wrapped_text_block(&mut buff, next_line_tap_stop, text);
-        write!(out, "{}", buff).unwrap();
-    }
+    /// Render the option listing, passing each entry to `emit` once it's rendered.
+    fn render_options_with<F>(&self, options: &Options, mut emit: F) -> io::Result<()>
+        where F: FnMut(&str) -> io::Result<()> {
+        if options.is_empty() {
+            return Ok(());
+        }
 
-    fn render_options(&self, buff: &mut String, options: &Options) {
         let left_pad = self.create_padding(self.get_left_padding());
         let desc_pad = self.create_padding(self.get_desc_padding());
 
-        let mut max = 0;
-        let mut prefix_list: Vec<String> = vec![];
-        let mut opt_list = options.get_options();
+        let mut opt_list = options.get_options_in_order();
 
         if let Some(cmp) = self.get_option_comparator() {
             opt_list.sort_by(|x, y| cmp(&x, &y));
         }
 
-        for option in opt_list.iter() {
-            let mut opt_buff = String::new();
+        // the width of the short option column, like `-s, `, to align the long options
+        let short_opt_width = opt_list.iter()
+            .filter_map(|option| option.get_opt())
+            .map(|opt| self.get_opt_prefix().len() + opt.chars().count())
+            .max()
+            .filter(|_| self.align_long_opts);
+
+        // count chars as the marker may be non-ASCII
+        let mut max = opt_list.iter()
+            .map(|option| self.option_prefix(option, &left_pad, short_opt_width).chars().count())
+            .max()
+            .unwrap_or(0);
+
+        if let Some(max_opt_width) = self.max_opt_width {
+            max = max.min(max_opt_width);
+        }
+        if let Some(min_opt_width) = self.min_opt_width {
+            max = max.max(min_opt_width);
+        }
 
-            opt_buff.push_str(&left_pad);
-            if option.get_opt().is_none() {
-                opt_buff.push_str(self.get_long_opt_prefix());
-                opt_buff.push_str(option.get_long_opt().unwrap());
-            } else {
-                opt_buff.push_str(self.get_opt_prefix());
-                opt_buff.push_str(option.get_opt().unwrap());
+        let sections = self.get_sections(options);
 
-                if option.has_long_opt() {
-                    opt_buff.push_str(", ");
-                    opt_buff.push_str(self.get_long_opt_prefix());
-                    opt_buff.push_str(option.get_long_opt().unwrap());
-                }
+        let mut first_line = true;
+        for section in sections.iter() {
+            let members: Vec<usize> = opt_list.iter().enumerate()
+                .filter(|(_, option)| option.get_section() == section.as_ref())
+                .map(|(i, _)| i)
+                .collect();
+            if members.is_empty() {
+                continue;
             }
 
-            if option.has_arg() {
-                let arg_name = option.get_arg_name();
-                if arg_name.is_some() && arg_name.as_ref().unwrap().is_empty() {
-                    opt_buff.push_str(" ");
-                } else {
-                    opt_buff.push_str(" ");
-                    let arg = if arg_name.is_some() { arg_name.unwrap() } else { self.get_arg_name() };
-                    opt_buff.push_str(&format!("<{}>", arg));
+            let mut buff = String::new();
+            if let Some(name) = section {
+                if !first_line {
+                    buff.push_str(self.get_newline());
+                    buff.push_str(self.get_newline());
                 }
+                self.render_wrapped_text(&mut buff, 0, name);
+                first_line = false;
             }
-            max = max.max(opt_buff.len());
-            prefix_list.push(opt_buff);
-        }
+            emit(&buff)?;
+
+            for i in members {
+                let mut buff = String::new();
+                if !first_line {
+                    buff.push_str(self.get_newline());
+                }
+                first_line = false;
 
-        let len = opt_list.len();
-        for (i, option) in opt_list.into_iter().enumerate() {
-            let mut opt_buff = String::from(prefix_list.get(i).unwrap());
+                let mut desc = opt_list[i].get_description().cloned().unwrap_or_default();
 
-            if opt_buff.len() < max {
-                opt_buff.push_str(&self.create_padding(max - opt_buff.len()));
+                if self.show_defaults {
+                    if let Some(default) = options.get_option_default(&opt_list[i]) {
+                        if !desc.is_empty() {
+                            desc.push(' ');
+                        }
+                        desc.push_str(&format!("(default: {})", default));
+                    }
+                }
+
+                let mut opt_buff = self.option_prefix(&opt_list[i], &left_pad, short_opt_width);
+
+                let len = opt_buff.chars().count();
+                if len < max {
+                    opt_buff.push_str(&self.create_padding(max - len));
+                } else if len > max && !desc.is_empty() {
+                    // the option name is wider than the column, start the description on the next line
+                    self.render_wrapped_text(&mut buff, max, &opt_buff);
+                    buff.push_str(self.get_newline());
+                    opt_buff = self.create_padding(max);
+                }
+
+                opt_buff.push_str(&desc_pad);
+
+                let next_line_tab_stop = max + self.get_desc_padding();
+
+                // only the description is truncated, a truncated word fits in a wrapped line
+                opt_buff.push_str(&self.apply_long_word_policy(&desc, self.get_width().saturating_sub(next_line_tab_stop)));
+
+                self.render_wrapped_text(&mut buff, next_line_tab_stop, &opt_buff);
+                emit(&buff)?;
             }
+        }
 
-            opt_buff.push_str(&desc_pad);
+        if !self.required_marker.is_empty() && opt_list.iter().any(|option| option.is_required()) {
+            let mut buff = String::new();
+            buff.push_str(self.get_newline());
+            buff.push_str(self.get_newline());
+            self.render_wrapped_text(&mut buff, 0, &format!("{}{} required option", left_pad, self.required_marker));
+            emit(&buff)?;
+        }
+        Ok(())
+    }
 
-            let next_line_tab_stop = max + self.get_desc_padding();
+    /// The option names column of `option` in the listing, like `    -f, --file <arg>`.
+    fn option_prefix(&self, option: &AnpOption, left_pad: &str, short_opt_width: Option<usize>) -> String {
+        let mut opt_buff = String::new();
 
-            if let Some(desc) = option.get_description() {
-                opt_buff.push_str(desc);
+        opt_buff.push_str(left_pad);
+        let negation = if option.is_negatable() { "[no-]" } else { "" };
+        if option.get_opt().is_none() {
+            if let Some(width) = short_opt_width {
+                opt_buff.push_str(&self.create_padding(width + 2));
             }
+            opt_buff.push_str(self.long_opt_prefix_of(option));
+            opt_buff.push_str(negation);
+            opt_buff.push_str(option.get_long_opt().unwrap());
+        } else {
+            opt_buff.push_str(self.get_opt_prefix());
+            opt_buff.push_str(option.get_opt().unwrap());
+
+            if option.has_long_opt() {
+                opt_buff.push(',');
+                if let Some(width) = short_opt_width {
+                    let len = self.get_opt_prefix().len() + option.get_opt().unwrap().chars().count();
+                    opt_buff.push_str(&self.create_padding(width - len));
+                }
+                opt_buff.push(' ');
+                opt_buff.push_str(self.long_opt_prefix_of(option));
+                opt_buff.push_str(negation);
+                opt_buff.push_str(option.get_long_opt().unwrap());
+            }
+        }
 
-            self.render_wrapped_text(buff, next_line_tab_stop, &opt_buff);
+        if let Some(placeholder) = self.arg_placeholder(option) {
+            opt_buff.push(' ');
+            opt_buff.push_str(&placeholder);
+        }
 
-            if i != len - 1 {
-                buff.push_str(self.get_newline());
+        if option.is_required() && !self.required_marker.is_empty() {
+            if !opt_buff.ends_with(' ') {
+                opt_buff.push(' ');
             }
+            opt_buff.push_str(&self.required_marker);
         }
+        opt_buff
     }
 
     fn render_wrapped_text(&self, buff: &mut String, mut next_line_tab_stop: usize, text: &str) {
+        if next_line_tab_stop >= self.get_width() || next_line_tab_stop == 0 {
+            next_line_tab_stop = 1;
+        }
+
         let mut pos = self.find_wrap_pos(text, self.get_width(), 0);
 
         if pos.is_none() {
@@ -443,14 +1018,17 @@ impl HelpFormatter {
         buff.push_str(&text[..pos.unwrap()].trim_end());
         buff.push_str(self.get_newline());
 
-        if next_line_tab_stop >= self.get_width() || next_line_tab_stop == 0 {
-            next_line_tab_stop = 1;
-        }
-
         let mut processing_text = text.to_string();
         let padding = self.create_padding(next_line_tab_stop);
         loop {
-            processing_text = format!("{}{}", &padding, &processing_text[pos.unwrap()..].trim());
+            // keep the indentation of lines the author broke explicitly
+            let rest = &processing_text[pos.unwrap()..];
+            let rest = if processing_text[..pos.unwrap()].ends_with('\n') {
+                rest.trim_end()
+            } else {
+                rest.trim()
+            };
+            processing_text = format!("{}{}", &padding, rest);
             pos = self.find_wrap_pos(&processing_text, self.get_width(), 0);
 
             if pos.is_none() {
@@ -458,8 +1036,13 @@ impl HelpFormatter {
                 return;
             }
 
-            if processing_text.len() > self.get_width() && pos == Some(next_line_tab_stop - 1) {
-                pos = Some(self.get_width());
+            if pos == Some(next_line_tab_stop - 1) {
+                // only the padding before the long word
+                pos = self.find_long_word_break(&processing_text, next_line_tab_stop, self.get_width());
+                if pos.is_none() {
+                    buff.push_str(&processing_text);
+                    return;
+                }
             }
 
             buff.push_str(&processing_text[..pos.unwrap()].trim_end());
@@ -467,6 +1050,24 @@ impl HelpFormatter {
         }
     }
 
+    /// Wrap the `text` at the width configured, all lines are indented by `first_indent`
+    /// spaces like the first line.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::HelpFormatter;
+    ///
+    /// let formatter = HelpFormatter::builder("test").set_width(12).build();
+    /// assert_eq!("  the quick\n  brown fox", formatter.wrap_text("the quick brown fox", 2));
+    /// ```
+    pub fn wrap_text(&self, text: &str, first_indent: usize) -> String {
+        let mut buff = String::new();
+        self.render_wrapped_text_block(&mut buff, first_indent,
+                                       &format!("{}{}", self.create_padding(first_indent), text));
+        buff
+    }
+
     /// Render a wrapped text block to the `buffer` with the max `width` configured.
     /// When text is wrapped, `next_line_tab_stop` number of space is appended.
     pub fn render_wrapped_text_block(&self, buffer: &mut String, next_line_tab_stop: usize, text: &str) {
@@ -475,7 +1076,733 @@ impl HelpFormatter {
             if i != 0 {
                 buffer.push_str(self.get_newline());
             }
+            let tab_stop = if next_line_tab_stop >= self.get_width() { 1 } else { next_line_tab_stop.max(1) };
+            let line = self.apply_long_word_policy(&line, self.get_width() - tab_stop);
             self.render_wrapped_text(buffer, next_line_tab_stop, &line);
         }
     }
+
+    /// Render the option schema of the [`Options`] as a JSON document.
+    ///
+    /// Options are listed in the order they were added to `options`, so the output
+    /// is stable across runs. Each option has the shape:
+    ///
+    /// ```txt
+    /// {"opt": "f", "long_opt": "file", "arg_name": "FILE", "description": "...",
+    ///  "required": false, "args": 1, "optional_arg": false, "allowed_values": null,
+    ///  "default_value": null, "section": null,
+    ///  "group": {"required": false, "options": ["f", "g"]}}
+    /// ```
+    ///
+    /// The `args` is `"unlimited"` for options accepting any number of arguments,
+    /// `{"min": 1, "max": 5}` for options with a range of arguments (`max` is `null`
+    /// if unbounded), and `group` is `null` if the option is not a member of any [`OptionGroup`].
+    pub fn render_help_json(&self, options: &Options) -> String {
+        let mut buff = String::from("{\"usage\":");
+        buff.push_str(&Util::escape_json(&self.full_cmd_syntax()));
+        buff.push_str(",\"header\":");
+        buff.push_str(&self.header.as_ref().map_or("null".to_string(), |h| Util::escape_json(h)));
+        buff.push_str(",\"footer\":");
+        buff.push_str(&self.footer.as_ref().map_or("null".to_string(), |f| Util::escape_json(f)));
+        buff.push_str(",\"options\":[");
+
+        for (i, option) in options.get_options_in_order().iter().enumerate() {
+            if i != 0 {
+                buff.push(',');
+            }
+            let json_or_null = |value: Option<&String>| {
+                value.map_or("null".to_string(), |v| Util::escape_json(v))
+            };
+
+            buff.push_str("{\"opt\":");
+            buff.push_str(&json_or_null(option.get_opt()));
+            buff.push_str(",\"long_opt\":");
+            buff.push_str(&json_or_null(option.get_long_opt()));
+            buff.push_str(",\"arg_name\":");
+            buff.push_str(&json_or_null(option.get_arg_name()));
+            buff.push_str(",\"description\":");
+            buff.push_str(&json_or_null(option.get_description()));
+            buff.push_str(&format!(",\"required\":{}", option.is_required()));
+            buff.push_str(",\"args\":");
+            buff.push_str(&match option.get_args() {
+                ArgCount::Fixed(n) => n.to_string(),
+                ArgCount::Uninitialized => "0".to_string(),
+                ArgCount::Unlimited => "\"unlimited\"".to_string(),
+                ArgCount::Range { min, max } => format!("{{\"min\":{},\"max\":{}}}",
+                                                        min, max.map_or("null".to_string(), |m| m.to_string())),
+            });
+            buff.push_str(&format!(",\"optional_arg\":{}", option.has_optional_arg()));
+            buff.push_str(",\"allowed_values\":");
+            if let Some(values) = option.get_allowed_values() {
+                let values: Vec<String> = values.iter().map(|v| Util::escape_json(v)).collect();
+                buff.push_str(&format!("[{}]", values.join(",")));
+            } else {
+                buff.push_str("null");
+            }
+            buff.push_str(",\"default_value\":");
+            buff.push_str(&json_or_null(options.get_option_default(option)));
+            buff.push_str(",\"section\":");
+            buff.push_str(&json_or_null(option.get_section()));
+            buff.push_str(",\"group\":");
+            if let Some(group) = options.get_option_group(option) {
+                let group = group.borrow();
+                let mut names = group.get_names();
+                names.sort();
+                let names: Vec<String> = names.into_iter().map(Util::escape_json).collect();
+                buff.push_str(&format!("{{\"required\":{},\"options\":[{}]}}",
+                                       group.is_required(), names.join(",")));
+            } else {
+                buff.push_str("null");
+            }
+            buff.push('}');
+        }
+
+        buff.push_str("]}");
+        buff
+    }
+
+    /// Render the help message as Markdown.
+    ///
+    /// The output has a `## Usage` section with the cmd syntax, followed by the header
+    /// and footer as paragraphs, and an `## Options` section listing options in a table.
+    /// Options in a section are listed in a separate table under a `###` heading.
+    ///
+    /// Unlike [`Self::print_help`], the text is not wrapped at [`Self::get_width`].
+    pub fn render_markdown(&self, options: &Options) -> String {
+        let escape = |text: &str| text.replace('|', "\\|").replace('\n', "<br>");
+        let mut buff = String::from("## Usage\n\n```\n");
+        let mut syntax = self.full_cmd_syntax();
+        if self.auto_usage {
+            for token in self.usage_tokens(options) {
+                if !syntax.is_empty() {
+                    syntax.push(' ');
+                }
+                syntax.push_str(&token);
+            }
+        }
+        buff.push_str(&syntax);
+        buff.push_str("\n```\n");
+
+        if let Some(header) = self.header.as_ref().filter(|h| !h.is_empty()) {
+            buff.push('\n');
+            buff.push_str(header);
+            buff.push('\n');
+        }
+
+        let mut opt_list = options.get_options_in_order();
+        if let Some(cmp) = self.get_option_comparator() {
+            opt_list.sort_by(|x, y| cmp(x, y));
+        }
+
+        if !opt_list.is_empty() {
+            buff.push_str("\n## Options\n");
+        }
+        for section in self.get_sections(options) {
+            let members: Vec<&AnpOption> = opt_list.iter()
+                .filter(|option| option.get_section() == section.as_ref())
+                .map(|option| option.deref())
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = section {
+                buff.push_str(&format!("\n### {}\n", name));
+            }
+            buff.push_str("\n| Option | Argument | Required | Description |\n");
+            buff.push_str("| --- | --- | --- | --- |\n");
+            for option in members {
+                let mut names = vec![];
+                if let Some(opt) = option.get_opt() {
+                    names.push(format!("`{}{}`", self.get_opt_prefix(), opt));
+                }
+                if let Some(long_opt) = option.get_long_opt() {
+                    names.push(format!("`{}{}`", self.long_opt_prefix_of(option), long_opt));
+                }
+
+                let arg = self.arg_placeholder(option).map_or(String::new(), |a| format!("`{}`", a));
+
+                buff.push_str(&format!("| {} | {} | {} | {} |\n",
+                                       names.join(", "),
+                                       arg,
+                                       if option.is_required() { "yes" } else { "" },
+                                       escape(option.get_description().map_or("", |d| d))));
+            }
+        }
+
+        if let Some(footer) = self.footer.as_ref().filter(|f| !f.is_empty()) {
+            buff.push('\n');
+            buff.push_str(footer);
+            buff.push('\n');
+        }
+        buff
+    }
+
+    /// Render the help message as a man page in roff, which can be saved as
+    /// `program.<section>` and viewed by `man`.
+    ///
+    /// The page has a `NAME` section with the `program` and the header, a `SYNOPSIS`
+    /// section with the cmd syntax, and an `OPTIONS` section listing each option in
+    /// a `.TP` paragraph. Options in a section are listed under a `.SS` subheading.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{HelpFormatter, Options};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("o", "output", true, "the output file").unwrap();
+    ///
+    /// let page = HelpFormatter::new("app [OPTIONS] FILE").render_manpage(&options, 1, "app");
+    /// assert!(page.starts_with(".TH \"APP\" \"1\"\n.SH NAME\napp\n.SH SYNOPSIS\n"));
+    /// assert!(page.contains(".TP\n\\fB\\-o\\fR, \\fB\\-\\-output\\fR \\fI<arg>\\fR\nthe output file\n"));
+    /// ```
+    pub fn render_manpage(&self, options: &Options, section: u8, program: &str) -> String {
+        let name = |name: &str| format!("\\fB{}\\fR", Util::escape_roff(name).replace('-', "\\-"));
+
+        let mut buff = format!(".TH \"{}\" \"{}\"\n", program.to_uppercase().replace('"', ""), section);
+        buff.push_str(".SH NAME\n");
+        buff.push_str(&Util::escape_roff(program).replace('-', "\\-"));
+        if let Some(header) = self.header.as_ref().filter(|h| !h.is_empty()) {
+            buff.push_str(" \\- ");
+            buff.push_str(&Util::escape_roff(header));
+        }
+        buff.push('\n');
+
+        buff.push_str(".SH SYNOPSIS\n");
+        let mut syntax = self.full_cmd_syntax();
+        if self.auto_usage {
+            for token in self.usage_tokens(options) {
+                if !syntax.is_empty() {
+                    syntax.push(' ');
+                }
+                syntax.push_str(&token);
+            }
+        }
+        buff.push_str(&Util::escape_roff(&syntax));
+        buff.push('\n');
+
+        let mut opt_list = options.get_options_in_order();
+        if let Some(cmp) = self.get_option_comparator() {
+            opt_list.sort_by(|x, y| cmp(x, y));
+        }
+
+        if !opt_list.is_empty() {
+            buff.push_str(".SH OPTIONS\n");
+        }
+        for section in self.get_sections(options) {
+            let members: Vec<&AnpOption> = opt_list.iter()
+                .filter(|option| option.get_section() == section.as_ref())
+                .map(|option| option.deref())
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = section {
+                buff.push_str(&format!(".SS {}\n", Util::escape_roff(&section)));
+            }
+            for option in members {
+                let mut names = vec![];
+                if let Some(opt) = option.get_opt() {
+                    names.push(name(&format!("{}{}", self.get_opt_prefix(), opt)));
+                }
+                if let Some(long_opt) = option.get_long_opt() {
+                    names.push(name(&format!("{}{}", self.long_opt_prefix_of(option), long_opt)));
+                }
+
+                buff.push_str(".TP\n");
+                buff.push_str(&names.join(", "));
+                if let Some(placeholder) = self.arg_placeholder(option) {
+                    buff.push_str(&format!(" \\fI{}\\fR", Util::escape_roff(&placeholder)));
+                }
+                buff.push('\n');
+                if let Some(desc) = option.get_description().filter(|d| !d.is_empty()) {
+                    buff.push_str(&Util::escape_roff(desc));
+                    buff.push('\n');
+                }
+            }
+        }
+        buff
+    }
+
+    /// Render a bash completion script for the [`Options`].
+    ///
+    /// The script registers a completion function for `program` with `complete -F`,
+    /// so it can be loaded by `eval "$(program --generate-completion bash)"` or saved
+    /// to the `bash_completion.d` directory.
+    ///
+    /// Long options taking arguments are completed with a trailing `=`. The argument
+    /// of an option is completed with its allowed values if any, otherwise by its
+    /// [`ValueHint`], defaulting to file names.
+    pub fn render_bash_completion(&self, options: &Options, program: &str) -> String {
+        let func_name: String = program.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let func_name = format!("_{}_completion", func_name);
+
+        let mut words = vec![];
+        let mut arg_cases = String::new();
+        for option in options.get_options_in_order().iter() {
+            let mut names = vec![];
+            if let Some(opt) = option.get_opt() {
+                words.push(format!("-{}", opt));
+                names.push(Util::quote_shell(&format!("-{}", opt)));
+            }
+            if let Some(long_opt) = option.get_long_opt() {
+                words.push(format!("--{}{}", long_opt, if option.has_arg() { "=" } else { "" }));
+                names.push(Util::quote_shell(&format!("--{}", long_opt)));
+            }
+            if let Some(negated_long_opt) = option.get_negated_long_opt() {
+                words.push(format!("--{}", negated_long_opt));
+            }
+
+            if option.has_arg() {
+                let candidates = match (option.get_allowed_values(), option.get_value_hint()) {
+                    (Some(values), _) => Some(format!("-W {}", Util::quote_shell(&values.join(" ")))),
+                    (None, None | Some(ValueHint::FilePath)) => Some("-f".to_string()),
+                    (None, Some(ValueHint::DirPath)) => Some("-d".to_string()),
+                    (None, Some(ValueHint::Hostname)) => Some("-A hostname".to_string()),
+                    (None, Some(ValueHint::Url | ValueHint::Other)) => None,
+                };
+                arg_cases.push_str(&format!("        {})\n", names.join("|")));
+                match candidates {
+                    Some(candidates) => arg_cases.push_str(
+                        &format!("            COMPREPLY=( $(compgen {} -- \"${{cur}}\") )\n", candidates)),
+                    None => arg_cases.push_str("            COMPREPLY=()\n"),
+                }
+                arg_cases.push_str("            return 0\n");
+                arg_cases.push_str("            ;;\n");
+            }
+        }
+
+        let mut buff = String::new();
+        buff.push_str(&format!("{}() {{\n", func_name));
+        buff.push_str("    local cur prev\n");
+        buff.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+        buff.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+        buff.push('\n');
+        buff.push_str("    # `--opt=value` is split into `--opt`, `=` and `value` by COMP_WORDBREAKS\n");
+        buff.push_str("    if [[ \"${cur}\" == \"=\" ]]; then\n");
+        buff.push_str("        cur=\"\"\n");
+        buff.push_str("    elif [[ \"${prev}\" == \"=\" && ${COMP_CWORD} -gt 1 ]]; then\n");
+        buff.push_str("        prev=\"${COMP_WORDS[COMP_CWORD-2]}\"\n");
+        buff.push_str("    fi\n");
+        buff.push('\n');
+        buff.push_str("    case \"${prev}\" in\n");
+        buff.push_str(&arg_cases);
+        buff.push_str("    esac\n");
+        buff.push('\n');
+        buff.push_str("    if [[ \"${cur}\" == -* ]]; then\n");
+        buff.push_str(&format!("        COMPREPLY=( $(compgen -W {} -- \"${{cur}}\") )\n",
+                               Util::quote_shell(&words.join(" "))));
+        buff.push_str("        if [[ \"${COMPREPLY[0]}\" == *= ]]; then\n");
+        buff.push_str("            compopt -o nospace\n");
+        buff.push_str("        fi\n");
+        buff.push_str("        return 0\n");
+        buff.push_str("    fi\n");
+        buff.push('\n');
+        buff.push_str("    COMPREPLY=( $(compgen -f -- \"${cur}\") )\n");
+        buff.push_str("}\n");
+        buff.push('\n');
+        buff.push_str(&format!("complete -F {} {}\n", func_name, Util::quote_shell(program)));
+        buff
+    }
+
+    /// The argument name of `option` in the completion, derived from its [`ValueHint`]
+    /// or allowed values if it has no argument name.
+    fn completion_arg_name<'a>(&'a self, option: &'a AnpOption) -> &'a str {
+        if let Some(arg_name) = option.get_arg_name().filter(|a| !a.is_empty() && *a != DEFAULT_ARG_NAME) {
+            return arg_name;
+        }
+        if option.get_allowed_values().is_some() {
+            return "value";
+        }
+        match option.get_value_hint() {
+            Some(ValueHint::FilePath) => "file",
+            Some(ValueHint::DirPath) => "directory",
+            Some(ValueHint::Hostname) => "host",
+            Some(ValueHint::Url) => "url",
+            Some(ValueHint::Other) | None => self.get_default_arg_name().unwrap_or(self.get_arg_name()),
+        }
+    }
+
+    /// Render a zsh completion script for the [`Options`].
+    ///
+    /// The script defines a `#compdef` function for `program` using `_arguments`,
+    /// it can be saved as `_program` to a directory in `$fpath`.
+    ///
+    /// The short and long name of an option exclude each other, and so do the
+    /// members of an [`OptionGroup`]. The argument of an option is completed with
+    /// its allowed values if any, otherwise by its [`ValueHint`], defaulting to file names.
+    pub fn render_zsh_completion(&self, options: &Options, program: &str) -> String {
+        let func_name: String = program.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let func_name = format!("_{}", func_name);
+
+        let names_of = |option: &AnpOption| {
+            let mut names = vec![];
+            if let Some(opt) = option.get_opt() {
+                names.push(format!("-{}", opt));
+            }
+            if let Some(long_opt) = option.get_long_opt() {
+                names.push(format!("--{}", long_opt));
+            }
+            names
+        };
+        let escape = |text: &str| {
+            text.replace('\\', "\\\\")
+                .replace('[', "\\[")
+                .replace(']', "\\]")
+        };
+
+        let mut specs = vec![];
+        for option in options.get_options_in_order().iter() {
+            let mut exclusions = vec![];
+            if let Some(group) = options.get_option_group(option) {
+                let mut members = group.borrow().get_options();
+                members.sort_by(|a, b| a.borrow().get_key().cmp(b.borrow().get_key()));
+                for member in members {
+                    exclusions.append(&mut names_of(&member.borrow()));
+                }
+            } else if option.get_opt().is_some() && option.has_long_opt() {
+                exclusions.append(&mut names_of(option));
+            }
+
+            let names: Vec<String> = names_of(option).into_iter()
+                .map(|name| {
+                    if !option.has_arg() {
+                        name
+                    } else if name.starts_with("--") {
+                        format!("{}=", name)
+                    } else {
+                        format!("{}+", name)
+                    }
+                })
+                .collect();
+
+            let exclusions = if exclusions.is_empty() {
+                String::new()
+            } else {
+                format!("({})", exclusions.join(" "))
+            };
+
+            let mut desc = String::new();
+            if let Some(description) = option.get_description() {
+                desc.push_str(&format!("[{}]", escape(description)));
+            }
+            if option.has_arg() {
+                let arg_name = self.completion_arg_name(option);
+                let action = match option.get_allowed_values() {
+                    Some(values) => {
+                        let values: Vec<String> = values.iter()
+                            .map(|v| escape(v).replace(' ', "\\ "))
+                            .collect();
+                        format!("({})", values.join(" "))
+                    }
+                    None => match option.get_value_hint() {
+                        None | Some(ValueHint::FilePath) => "_files".to_string(),
+                        Some(ValueHint::DirPath) => "_directories".to_string(),
+                        Some(ValueHint::Hostname) => "_hosts".to_string(),
+                        Some(ValueHint::Url) => "_urls".to_string(),
+                        Some(ValueHint::Other) => " ".to_string(),
+                    },
+                };
+                let sep = if option.has_optional_arg() { "::" } else { ":" };
+                desc.push_str(&format!("{}{}:{}", sep, escape(arg_name).replace(':', "\\:"), action));
+            }
+
+            if names.len() > 1 {
+                let desc = if desc.is_empty() { desc } else { Util::quote_shell(&desc) };
+                specs.push(format!("{}{{{}}}{}", Util::quote_shell(&exclusions), names.join(","), desc));
+            } else {
+                specs.push(Util::quote_shell(&format!("{}{}{}", exclusions, names[0], desc)));
+            }
+        }
+        specs.push(Util::quote_shell("*: :_files"));
+
+        let mut buff = String::new();
+        buff.push_str(&format!("#compdef {}\n", program));
+        buff.push('\n');
+        buff.push_str(&format!("{}() {{\n", func_name));
+        buff.push_str("    _arguments -s \\\n");
+        for (i, spec) in specs.iter().enumerate() {
+            buff.push_str("        ");
+            buff.push_str(spec);
+            buff.push_str(if i != specs.len() - 1 { " \\\n" } else { "\n" });
+        }
+        buff.push_str("}\n");
+        buff.push('\n');
+        buff.push_str(&format!("{} \"$@\"\n", func_name));
+        buff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::{HelpFormatter, LongWordPolicy};
+    use crate::option::{AnpOption, OptionGroup, Options, ValueHint};
+
+    fn wrap(width: usize, text: &str) -> String {
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_width(width);
+        let mut buff = String::new();
+        formatter.render_wrapped_text_block(&mut buff, 2, text);
+        buff
+    }
+
+    #[test]
+    fn test_wrap_multibyte_text() {
+        assert_eq!("café crème\n  brûlée", wrap(12, "café crème brûlée"));
+        assert_eq!("café\n  crèm\n  e\n  brûl\n  ée", wrap(6, "café crème brûlée"));
+        assert_eq!("a → b\n  → c", wrap(5, "a → b → c"));
+        assert_eq!("éééé", wrap(4, "éééé"));
+    }
+
+    #[test]
+    fn test_wrap_multibyte_text_without_spaces() {
+        assert_eq!("中文中\n  文\n  中\n  文", wrap(3, "中文中文中文"));
+        assert_eq!("中文\n  中文中文中文", wrap(8, "中文 中文中文中文"));
+        assert_eq!("→→→→\n  →→", wrap(4, "→→→→→→"));
+        assert_eq!("ab\n é", wrap(2, "abé"));
+    }
+
+    #[test]
+    fn test_empty_arg_name() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("f").long_option("file").has_arg(true).arg_name("")
+            .required(true).desc("input").build().unwrap());
+        options.add_option(AnpOption::builder().option("o").has_arg(true).desc("output").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_auto_usage(true);
+        assert_eq!("usage: test -f [-o <arg>]", formatter.usage_to_string(&options));
+        assert_eq!("    -f, --file    input\n    -o <arg>      output", formatter.options_to_string(&options));
+    }
+
+    #[test]
+    fn test_arg_name_format() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("f").has_arg(true).arg_name("FILE").desc("input").build().unwrap());
+        options.add_option(AnpOption::builder().option("v").desc("verbose").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_auto_usage(true);
+        formatter.set_arg_name_format("[", "]");
+        assert_eq!("usage: test [-f [FILE]] [-v]", formatter.usage_to_string(&options));
+        assert_eq!("    -f [FILE]    input\n    -v           verbose", formatter.options_to_string(&options));
+    }
+
+    #[test]
+    fn test_empty_cmd_syntax() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("v").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("");
+        let mut out = vec![];
+        formatter.print_usage(&mut out).unwrap();
+        assert_eq!("usage:", String::from_utf8(out).unwrap());
+        let mut out = vec![];
+        formatter.print_usage_with_options(&mut out, &options).unwrap();
+        assert_eq!("usage: [-v]", String::from_utf8(out).unwrap());
+
+        formatter.set_program_name("prog");
+        let mut out = vec![];
+        formatter.print_usage_with_options(&mut out, &options).unwrap();
+        assert_eq!("usage: prog [-v]", String::from_utf8(out).unwrap());
+        assert!(formatter.render_help_json(&options).starts_with("{\"usage\":\"prog\","));
+    }
+
+    #[test]
+    fn test_group_note() {
+        let mut options = Options::new();
+        options.add_option_group(OptionGroup::new()
+            .required(true)
+            .note("(choose exactly one)")
+            .add_option(AnpOption::builder().option("a").build().unwrap())
+            .add_option(AnpOption::builder().option("b").build().unwrap()));
+        options.add_option(AnpOption::builder().option("v").desc("verbose").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_auto_usage(true);
+        assert!(formatter.help_to_string(&options).starts_with("usage: test (-a | -b) (choose exactly one) [-v]\n"));
+
+        formatter.set_width(30);
+        assert_eq!("usage: test (-a | -b) (choose\n            exactly one) [-v]", formatter.usage_to_string(&options));
+    }
+
+    #[test]
+    fn test_multi_arg_separator() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().long_option("color").number_of_args(3).arg_name("C").desc("rgb")
+            .build().unwrap());
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_auto_usage(true);
+        assert_eq!("usage: test [--color <C>]", formatter.usage_to_string(&options));
+
+        formatter.set_multi_arg_separator(",");
+        assert_eq!("usage: test [--color <C>,<C>,<C>]", formatter.usage_to_string(&options));
+        assert_eq!("    --color <C>,<C>,<C>    rgb", formatter.options_to_string(&options));
+
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("D").property_option(true).arg_name("property=value")
+            .build().unwrap());
+        options.add_option(AnpOption::builder().option("p").number_of_args(2).value_separator(':').build().unwrap());
+        assert_eq!("usage: test [-D <property=value>] [-p <arg>]", formatter.usage_to_string(&options));
+    }
+
+    #[test]
+    fn test_wrap_usage() {
+        let mut options = Options::new();
+        for name in ["alpha", "bravo", "charlie", "delta", "echo"] {
+            options.add_option(AnpOption::builder().long_option(name).has_arg(true).arg_name("VALUE").build().unwrap());
+        }
+        options.add_option_group(OptionGroup::new()
+            .add_option(AnpOption::builder().option("x").build().unwrap())
+            .add_option(AnpOption::builder().option("y").build().unwrap()));
+
+        let mut formatter = HelpFormatter::new("tool");
+        formatter.set_auto_usage(true);
+        formatter.set_width(40);
+        assert_eq!("usage: tool [--alpha <VALUE>]\n            [--bravo <VALUE>]\n            \
+                    [--charlie <VALUE>]\n            [--delta <VALUE>]\n            \
+                    [--echo <VALUE>] [-x | -y]",
+                   formatter.usage_to_string(&options));
+    }
+
+    #[test]
+    fn test_usage_option_groups() {
+        let group = |required: bool, a: &str, b: &str| OptionGroup::new()
+            .required(required)
+            .add_option(AnpOption::builder().option(a).build().unwrap())
+            .add_option(AnpOption::builder().option(b).build().unwrap());
+        let mut options = Options::new();
+        options.add_option_group(group(true, "a", "b"));
+        options.add_option_group(group(false, "x", "y"));
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_auto_usage(true);
+        assert_eq!("usage: test (-a | -b) [-x | -y]", formatter.usage_to_string(&options));
+    }
+
+    #[test]
+    fn test_explicit_line_breaks() {
+        let mut options = Options::new();
+        options.add_option2("f", "file", true, "line one\nline two\n  - item").unwrap();
+        let formatter = HelpFormatter::builder("test").set_width(40).build();
+        let mut buff = Vec::new();
+        formatter.print_options(&mut buff, &options).unwrap();
+        assert_eq!("    -f, --file <arg>    line one\n                        line two\n                          - item",
+                   String::from_utf8(buff).unwrap());
+    }
+
+    #[test]
+    fn test_completion_value_hint() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("d").long_option("dir").has_arg(true)
+            .value_hint(ValueHint::DirPath).build().unwrap());
+        options.add_option(AnpOption::builder().long_option("host").has_arg(true)
+            .value_hint(ValueHint::Hostname).build().unwrap());
+        options.add_option(AnpOption::builder().long_option("name").has_arg(true)
+            .value_hint(ValueHint::Other).build().unwrap());
+        options.add_option2("f", "file", true, "").unwrap();
+        assert_eq!(Some(ValueHint::DirPath), options.get_option("dir").unwrap().borrow().get_value_hint());
+
+        let formatter = HelpFormatter::new("app");
+        let bash = formatter.render_bash_completion(&options, "app");
+        assert!(bash.contains("        '-d'|'--dir')\n            COMPREPLY=( $(compgen -d -- \"${cur}\") )\n"));
+        assert!(bash.contains("        '--host')\n            COMPREPLY=( $(compgen -A hostname -- \"${cur}\") )\n"));
+        assert!(bash.contains("        '--name')\n            COMPREPLY=()\n"));
+        assert!(bash.contains("        '-f'|'--file')\n            COMPREPLY=( $(compgen -f -- \"${cur}\") )\n"));
+
+        let zsh = formatter.render_zsh_completion(&options, "app");
+        assert!(zsh.contains(":directory:_directories'"));
+        assert!(zsh.contains("'--host=:host:_hosts'"));
+        assert!(zsh.contains("'--name=:arg: '"));
+        assert!(zsh.contains(":arg:_files'"));
+
+        // the default arg name is only a fallback of completion
+        options.add_option(AnpOption::builder().long_option("out").has_arg(true).arg_name("arg")
+            .value_hint(ValueHint::FilePath).build().unwrap());
+        let formatter = HelpFormatter::builder("app").set_default_arg_name("value").build();
+        let zsh = formatter.render_zsh_completion(&options, "app");
+        assert!(zsh.contains("'--out=:file:_files'"));
+        assert!(zsh.contains("'--name=:value: '"));
+        assert!(formatter.options_to_string(&options).contains("--name <arg>"));
+    }
+
+    #[test]
+    fn test_long_word_policy() {
+        let url = format!("https://example.com/{}", "a".repeat(180));
+        let mut options = Options::new();
+        options.add_option2("u", "url", true, &format!("see {} for details", url)).unwrap();
+        let render = |policy: LongWordPolicy| {
+            let formatter = HelpFormatter::builder("test").set_width(40).set_long_word_policy(policy).build();
+            let mut buff = Vec::new();
+            formatter.print_options(&mut buff, &options).unwrap();
+            String::from_utf8(buff).unwrap()
+        };
+
+        let broken = render(LongWordPolicy::Break);
+        assert!(broken.starts_with("    -u, --url <arg>    see\n                       https://example.c\n"));
+        assert!(broken.lines().all(|line| line.chars().count() <= 40));
+        assert!(broken.lines().map(|line| line.trim()).collect::<String>().contains(&url));
+
+        assert_eq!(format!("    -u, --url <arg>    see\n                       {}\n                       for details", url),
+                   render(LongWordPolicy::Overflow));
+
+        assert_eq!("    -u, --url <arg>    see\n                       https://exampl...\n                       for details",
+                   render(LongWordPolicy::Truncate("...".to_string())));
+
+        // the option names are never truncated
+        let mut options = Options::new();
+        options.add_option2("l", "lengthy-option-xyz", true, "a supercalifragilisticexpialidocious word").unwrap();
+        options.add_option2("x", "x", false, "").unwrap();
+        let formatter = HelpFormatter::builder("test").set_width(60)
+            .set_long_word_policy(LongWordPolicy::Truncate("...".to_string())).build();
+        assert_eq!("    -l, --lengthy-option-xyz <arg>    a\n                                      supercalifragilisti...\n                                      word\n    -x, --x",
+                   formatter.options_to_string(&options));
+
+        // a word is kept if there is no room before the suffix
+        assert_eq!("abcdef", formatter.truncate_long_words("abcdef", 2, "..."));
+    }
+
+    #[test]
+    fn test_render_manpage() {
+        let mut options = Options::new();
+        options.add_option2("v", "verbose", false, "print verbosely").unwrap();
+        options.add_option(AnpOption::builder().long_option("define").has_arg(true).arg_name("KEY")
+            .desc(".hidden \\n escape").section("Advanced").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("my-app [OPTIONS]");
+        formatter.set_header("do something");
+        let page = formatter.render_manpage(&options, 8, "my-app");
+        assert_eq!(".TH \"MY-APP\" \"8\"\n\
+                    .SH NAME\nmy\\-app \\- do something\n\
+                    .SH SYNOPSIS\nmy-app [OPTIONS]\n\
+                    .SH OPTIONS\n\
+                    .TP\n\\fB\\-v\\fR, \\fB\\-\\-verbose\\fR\nprint verbosely\n\
+                    .SS Advanced\n\
+                    .TP\n\\fB\\-\\-define\\fR \\fI<KEY>\\fR\n\\&.hidden \\en escape\n", page);
+    }
+
+    #[test]
+    fn test_write_options_streaming() {
+        let mut options = Options::new();
+        options.add_option2("v", "verbose", false, "print verbosely").unwrap();
+        options.add_option(AnpOption::builder().long_option("output").has_arg(true).required(true)
+            .desc("the output file, which is created if it doesn't exist").build().unwrap());
+        options.add_option(AnpOption::builder().option("x").section("Advanced").desc("enable x").build().unwrap());
+
+        let mut formatter = HelpFormatter::new("test");
+        formatter.set_width(40);
+        formatter.set_required_marker("*");
+        let mut buffered = Vec::new();
+        formatter.print_options(&mut buffered, &options).unwrap();
+        let mut streamed = Vec::new();
+        formatter.write_options_streaming(&mut streamed, &options).unwrap();
+        assert_eq!(buffered, streamed);
+    }
 }
@@ -88,13 +88,13 @@
 //! }
 //! let cmd = cmd.unwrap();
 //!
-//! let files = cmd.get_arg_list();
-//! if files.len() <= 1 {
+//! let files = cmd.positional_range(0);
+//! if files.is_empty() {
 //!     eprintln!("missing option <file>");
-//!     formatter.print_help(&mut stdout(), &options);
+//!     formatter.print_help(&mut stdout(), &options).unwrap();
 //!     exit(1);
 //! } else {
-//!     println!("processing file: {:?}", &files[1..]);
+//!     println!("processing file: {:?}", files);
 //! }
 //! if cmd.has_option("d") {
 //!     let datetime = SystemTime::now()
@@ -108,10 +108,10 @@
 //! }
 //! ```
 
-pub use cmd::CommandLine;
-pub use error::ParseErr;
-pub use format::HelpFormatter;
-pub use option::{AnpOption, OptionBuilder, OptionGroup, Options};
+pub use cmd::{CommandLine, ValueSource};
+pub use error::{ParseErr, ParseErrKind, TokenPosition};
+pub use format::{HelpFormatter, HelpFormatterBuilder, LongWordPolicy};
+pub use option::{AnpOption, OptionBuilder, OptionGroup, Options, ValueHint};
 pub use parser::{DefaultParser, Parser, ParserBuilder};
 
 mod format;
@@ -1,9 +1,9 @@
 use std::{cell::RefCell, collections::HashMap, fmt::Display, hash::Hash, rc::Rc, str::FromStr, vec};
 use std::cell::Ref;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Formatter, Pointer};
 use std::hash::Hasher;
-use std::ops::Deref;
+use std::ops::{Bound, Deref};
 
 use crate::error::OptionErr;
 use crate::util::{OptionValidator, Util};
@@ -13,6 +13,10 @@ pub enum ArgCount {
     Fixed(usize),
     Uninitialized,
     Unlimited,
+    Range {
+        min: usize,
+        max: Option<usize>,
+    },
 }
 
 impl ArgCount {
@@ -44,6 +48,48 @@ impl ArgCount {
             _ => false,
         }
     }
+
+    pub fn is_range(&self) -> bool {
+        matches!(self, ArgCount::Range { .. })
+    }
+
+    /// Get the max number of arguments, [`None`] if there is no upper bound.
+    pub fn get_max(&self) -> Option<usize> {
+        match self {
+            ArgCount::Fixed(n) => Some(*n),
+            ArgCount::Range { max, .. } => *max,
+            _ => None,
+        }
+    }
+
+    /// Get the min number of arguments required.
+    pub fn get_min(&self) -> usize {
+        match self {
+            ArgCount::Range { min, .. } => *min,
+            _ => 0,
+        }
+    }
+}
+
+/// The kind of value an option takes, used by the generated shell completion,
+/// see [`OptionBuilder::value_hint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueHint {
+    FilePath,
+    DirPath,
+    Hostname,
+    Url,
+    Other,
+}
+
+/// A user defined check on a single option value, see [`OptionBuilder::validator`].
+#[derive(Clone)]
+struct ValueValidator(Rc<dyn Fn(&str) -> Result<(), String>>);
+
+impl std::fmt::Debug for ValueValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ValueValidator")
+    }
 }
 
 /// The `AnpOption` represents a single option.
@@ -77,8 +123,23 @@ pub struct AnpOption {
     required: bool,
     optional_arg: bool,
     arg_count: ArgCount,
-    value_sep: Option<char>,
+    value_sep: Option<String>,
+    section: Option<String>,
+    int_range: Option<(i64, i64)>,
+    allowed_values: Option<Vec<String>>,
+    validator: Option<ValueValidator>,
+    default_value: Option<String>,
+    property: bool,
+    negatable: bool,
+    negated: bool,
+    single_hyphen_long: bool,
+    trim_values: bool,
+    stop_token: Option<String>,
+    eager: bool,
+    value_hint: Option<ValueHint>,
+    trailing_var_arg: bool,
     values: Vec<String>,
+    raw_values: Vec<String>,
 }
 
 /// An builder struct for [`AnpOption`].
@@ -90,7 +151,20 @@ pub struct OptionBuilder {
     required: bool,
     optional_arg: bool,
     arg_count: ArgCount,
-    value_sep: Option<char>,
+    value_sep: Option<String>,
+    section: Option<String>,
+    int_range: Option<(i64, i64)>,
+    allowed_values: Option<Vec<String>>,
+    validator: Option<ValueValidator>,
+    default_value: Option<String>,
+    property: bool,
+    negatable: bool,
+    single_hyphen_long: bool,
+    trim_values: bool,
+    stop_token: Option<String>,
+    eager: bool,
+    value_hint: Option<ValueHint>,
+    trailing_var_arg: bool,
 }
 
 impl OptionBuilder {
@@ -105,7 +179,8 @@ impl OptionBuilder {
     /// The valid option name:
     /// - for a single char - `alphabetic` only
     /// - for multiple chars - `alphabetic`, `"@"`, `"?"`
-    pub fn build(self) -> Result<AnpOption, OptionErr> {
+    /// - for long option - no whitespaces or `"="`, and not starting with `"-"`
+    pub fn build(mut self) -> Result<AnpOption, OptionErr> {
         if self.option.is_none() && self.long_option.is_none() {
             return Err(OptionErr::of(None, "either opt or longOpt must be specified"));
         }
@@ -113,11 +188,28 @@ impl OptionBuilder {
             OptionValidator::validate(option)?;
         }
         if let Some(ref long_option) = self.long_option {
-            if long_option.is_empty() {
-                return Err(OptionErr::of(None, "longOpt cannot be blank"));
+            OptionValidator::validate_long(long_option)?;
+        }
+        if let ArgCount::Range { min, max: Some(max) } = self.arg_count {
+            if max < min {
+                return Err(OptionErr::of(None, &format!("max args {} is less than min args {}", max, min)));
+            }
+        }
+        if self.negatable && self.long_option.is_none() {
+            return Err(OptionErr::of(None, "negatable option must have longOpt"));
+        }
+        if self.single_hyphen_long {
+            if self.long_option.as_ref().filter(|l| l.chars().count() >= 2).is_none() {
+                return Err(OptionErr::of(None, "single hyphen long option must have longOpt of multiple chars"));
+            }
+            if self.negatable {
+                return Err(OptionErr::of(None, "single hyphen long option cannot be negatable"));
             }
         }
-        Ok(AnpOption {
+        if self.trailing_var_arg {
+            self.arg_count = ArgCount::Unlimited;
+        }
+        let option = AnpOption {
             option: self.option,
             long_option: self.long_option,
             arg_name: self.arg_name,
@@ -126,8 +218,27 @@ impl OptionBuilder {
             arg_count: self.arg_count,
             value_sep: self.value_sep,
             optional_arg: self.optional_arg,
+            section: self.section,
+            int_range: self.int_range,
+            allowed_values: self.allowed_values,
+            validator: self.validator,
+            default_value: self.default_value,
+            property: self.property,
+            negatable: self.negatable,
+            negated: false,
+            single_hyphen_long: self.single_hyphen_long,
+            trim_values: self.trim_values,
+            stop_token: self.stop_token,
+            eager: self.eager,
+            value_hint: self.value_hint,
+            trailing_var_arg: self.trailing_var_arg,
             values: Vec::new(),
-        })
+            raw_values: Vec::new(),
+        };
+        if option.negatable && option.has_arg() {
+            return Err(OptionErr::of(Some(&option), "negatable option cannot have args"));
+        }
+        Ok(option)
     }
 
     /// Set the argument name of the option.
@@ -185,6 +296,18 @@ impl OptionBuilder {
         self
     }
 
+    /// Set the option to having at least `min` and at most `max` number of arguments.
+    /// If `max` is [`None`], the option accepts unlimited number of arguments.
+    ///
+    /// The parser stops passing values to the option once `max` is reached, and fails
+    /// if less than `min` values are passed, unless the argument is optional.
+    ///
+    /// Also see [`Self::has_args`] and [`Self::number_of_args`]
+    pub fn args_range(mut self, min: usize, max: Option<usize>) -> Self {
+        self.arg_count = ArgCount::Range { min, max };
+        self
+    }
+
     /// Whether argument(s) is optional.
     pub fn optional_arg(mut self, is_optional: bool) -> Self {
         self.optional_arg = is_optional;
@@ -202,7 +325,303 @@ impl OptionBuilder {
     /// For example, when the value separator set to `,`, the option value `-v=1,2,3`
     /// is parsed into three values.
     pub fn value_separator(mut self, value_sep: char) -> Self {
-        self.value_sep = Some(value_sep);
+        self.value_sep = Some(value_sep.to_string());
+        self
+    }
+
+    /// Set the value separator for the option, which can be multiple chars.
+    ///
+    /// For example, when the value separator set to `::`, the option value `-p a::b::c`
+    /// is parsed into three values. Also see [`Self::value_separator`].
+    pub fn value_separator_str(mut self, value_sep: &str) -> Self {
+        self.value_sep = Some(value_sep.to_owned());
+        self
+    }
+
+    /// Set the section the option belongs to.
+    ///
+    /// Options in the same section are listed together under a heading
+    /// line `name` in the help message.
+    pub fn section(mut self, name: &str) -> Self {
+        self.section = Some(name.trim().to_owned());
+        self
+    }
+
+    /// Restrict the option values to integers within `min..=max`.
+    ///
+    /// Each value is checked when it's passed to the option, values split by the
+    /// value separator are checked independently.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder()
+    ///     .long_option("threads")
+    ///     .has_arg(true)
+    ///     .int_range(1, 64)
+    ///     .build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert!(parser.parse_args(&options, &["--threads", "8"]).is_ok());
+    /// assert!(parser.parse_args(&options, &["--threads", "128"]).is_err());
+    /// assert!(parser.parse_args(&options, &["--threads", "many"]).is_err());
+    /// ```
+    pub fn int_range(mut self, min: i64, max: i64) -> Self {
+        self.int_range = Some((min, max));
+        self
+    }
+
+    /// Set the default value of the option, which is used when the option is not
+    /// passed to command line.
+    ///
+    /// For option without argument, the option is considered present if the default
+    /// value is `yes`, `true`, `on` or `1`, and absent if it's `no`, `false`, `off` or `0`,
+    /// ignoring case. Other values are rejected with [`crate::ParseErr::ProcessingErr`] when parsing.
+    ///
+    /// If the defaults of [`Options`] also have a value for the option, that value
+    /// takes precedence over the default value set here.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder()
+    ///     .long_option("threads")
+    ///     .has_arg(true)
+    ///     .default_value("4")
+    ///     .build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// let cmd = parser.parse_args(&options, &["app"]).unwrap();
+    /// assert_eq!(4, cmd.get_expected_value::<usize>("threads"));
+    /// ```
+    pub fn default_value(mut self, value: &str) -> Self {
+        self.default_value = Some(value.to_owned());
+        self
+    }
+
+    /// Set whether the option is a property option like `-Dkey=value`.
+    ///
+    /// A property option takes a key and an optional value separated by `=`, it can be
+    /// passed multiple times, and the properties are retrieved by
+    /// [`crate::CommandLine::get_option_properties`]. It implies `number_of_args(2)`,
+    /// `value_separator('=')` and `optional_arg(true)`, so [`AnpOption::has_arg`] is `true`.
+    ///
+    /// The short option name must be set. A token like `-Dkey=value` is always parsed as
+    /// the property `key=value`, rather than concatenated options `-D -k -e ...`, unless
+    /// it exactly matches another option name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("D").property_option(true).build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// let cmd = parser.parse_args(&options, &["-Dfoo=bar", "-Dbaz=qux", "-Dflag"]).unwrap();
+    /// let properties = cmd.get_option_properties("D");
+    /// assert_eq!("bar", properties["foo"]);
+    /// assert_eq!("qux", properties["baz"]);
+    /// assert_eq!("true", properties["flag"]);
+    /// ```
+    pub fn property_option(mut self, property: bool) -> Self {
+        self.property = property;
+        if property {
+            self.arg_count = ArgCount::Fixed(2);
+            self.value_sep = Some("=".to_string());
+            self.optional_arg = true;
+        }
+        self
+    }
+
+    /// Set whether the option can be negated by `--no-<long_option>`.
+    ///
+    /// The negatable option must have a long option name and no argument. The negated
+    /// form is recorded as the option, so [`crate::CommandLine::has_option`] is `true`
+    /// for both forms. Use [`crate::CommandLine::get_flag`] to know which form is passed,
+    /// rather than `has_option`. The negated form
+    /// must be passed in full, it's not considered when partially matching long options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("color").negatable(true).build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert_eq!(Some(true), parser.parse_args(&options, &["--color"]).unwrap().get_flag("color"));
+    /// assert_eq!(Some(false), parser.parse_args(&options, &["--no-color"]).unwrap().get_flag("color"));
+    /// assert_eq!(None, parser.parse_args(&options, &[""; 0]).unwrap().get_flag("color"));
+    /// ```
+    pub fn negatable(mut self, negatable: bool) -> Self {
+        self.negatable = negatable;
+        self
+    }
+
+    /// Set whether the long option is passed with a single hyphen, like `-projecthelp`.
+    ///
+    /// The option must have a long option name of multiple chars. It's only recognized
+    /// in the single hyphen form, which is never split into concatenated short options,
+    /// and the help message shows it with a single hyphen.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("projecthelp").single_hyphen_long(true).build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert!(parser.parse_args(&options, &["-projecthelp"]).unwrap().has_option("projecthelp"));
+    /// assert!(parser.parse_args(&options, &["--projecthelp"]).is_err());
+    /// ```
+    pub fn single_hyphen_long(mut self, single_hyphen_long: bool) -> Self {
+        self.single_hyphen_long = single_hyphen_long;
+        self
+    }
+
+    /// Set whether trim the leading and trailing whitespaces of the values.
+    ///
+    /// Each value is trimmed after splitting by the value separator, and before
+    /// validation. The raw values are kept as they were passed.
+    pub fn trim_values(mut self, trim_values: bool) -> Self {
+        self.trim_values = trim_values;
+        self
+    }
+
+    /// Set the token that ends the values of the option, like `;`.
+    ///
+    /// An option taking multiple values collects all following arguments, the stop token
+    /// ends the values so the arguments after it are positional arguments. The stop
+    /// token itself is neither a value nor an argument.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().long_option("files").has_args().stop_token(";").build().unwrap());
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["--files", "a", "b", ";", "extra"]).unwrap();
+    /// assert_eq!(2, cmd.get_values::<String>("files").unwrap().len());
+    /// assert_eq!(vec!["extra"], cmd.get_arg_list());
+    /// ```
+    pub fn stop_token(mut self, token: &str) -> Self {
+        self.stop_token = Some(token.to_owned());
+        self
+    }
+
+    /// Set whether the option is eager, like `--help`.
+    ///
+    /// When an eager option is passed, the parser skips the checks of required options
+    /// and missing arguments, so the option can be handled without passing the others.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("f").required(true).has_arg(true).build().unwrap());
+    /// options.add_option(AnpOption::builder().long_option("help").eager(true).build().unwrap());
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["--help"]).unwrap();
+    /// assert!(cmd.has_option("help"));
+    /// ```
+    pub fn eager(mut self, eager: bool) -> Self {
+        self.eager = eager;
+        self
+    }
+
+    /// Set whether the option captures all remaining tokens, like `run` of
+    /// `mytool run cmd -v --flag`.
+    ///
+    /// Once the option is passed, every following token is added as its value verbatim,
+    /// even `-x` and `--`, unless it's the [`OptionBuilder::stop_token`]. The option
+    /// takes unlimited arguments, and an [`Options`] can have at most one such option.
+    /// A second one is rejected by [`Options::try_add_option`] and [`Options::validate`],
+    /// or by the parser with [`crate::ParseErr::ProcessingErr`] if added by [`Options::add_option`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("v").build().unwrap());
+    /// options.add_option(AnpOption::builder().long_option("run").trailing_var_arg(true).build().unwrap());
+    ///
+    /// let cmd = DefaultParser::builder().build().parse_args(&options, &["-v", "--run", "ls", "-l"]).unwrap();
+    /// assert_eq!(vec!["ls", "-l"], cmd.get_values::<String>("run").unwrap().into_iter().map(|v| v.unwrap()).collect::<Vec<_>>());
+    /// ```
+    pub fn trailing_var_arg(mut self, trailing_var_arg: bool) -> Self {
+        self.trailing_var_arg = trailing_var_arg;
+        self
+    }
+
+    /// Set the kind of value the option takes, so the generated shell completion
+    /// completes it with files, directories, host names and so on.
+    ///
+    /// The hint is ignored by the parser, and the allowed values take precedence
+    /// over it in completion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, ValueHint};
+    ///
+    /// let option = AnpOption::builder().long_option("out-dir").has_arg(true).value_hint(ValueHint::DirPath).build().unwrap();
+    /// assert_eq!(Some(ValueHint::DirPath), option.get_value_hint());
+    /// ```
+    pub fn value_hint(mut self, hint: ValueHint) -> Self {
+        self.value_hint = Some(hint);
+        self
+    }
+
+    /// Restrict the option values to one of `values`.
+    ///
+    /// The allowed values are also offered as candidates by the generated shell completion.
+    pub fn allowed_values(mut self, values: &[&str]) -> Self {
+        self.allowed_values = Some(values.iter().map(|v| v.to_string()).collect());
+        self
+    }
+
+    /// Set a custom validator for the option values.
+    ///
+    /// The validator is called for every value after it's split by the value separator,
+    /// so each element of the final value list is checked. An `Err(msg)` returned by
+    /// the validator fails the parsing with the message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder()
+    ///     .long_option("level")
+    ///     .has_arg(true)
+    ///     .value_separator(',')
+    ///     .validator(|v| if v.starts_with('L') { Ok(()) } else { Err(format!("invalid level '{}'", v)) })
+    ///     .build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder().build();
+    /// assert!(parser.parse_args(&options, &["--level", "L1"]).is_ok());
+    /// assert!(parser.parse_args(&options, &["--level", "X1"]).is_err());
+    /// ```
+    pub fn validator(mut self, f: impl Fn(&str) -> Result<(), String> + 'static) -> Self {
+        self.validator = Some(ValueValidator(Rc::new(f)));
         self
     }
 }
@@ -219,6 +638,19 @@ impl AnpOption {
             arg_count: ArgCount::Uninitialized,
             value_sep: None,
             optional_arg: false,
+            section: None,
+            int_range: None,
+            allowed_values: None,
+            validator: None,
+            default_value: None,
+            property: false,
+            negatable: false,
+            single_hyphen_long: false,
+            trim_values: false,
+            stop_token: None,
+            eager: false,
+            value_hint: None,
+            trailing_var_arg: false,
         }
     }
 
@@ -233,6 +665,7 @@ impl AnpOption {
     pub fn has_arg(&self) -> bool {
         self.arg_count.is_unlimited()
             || (self.arg_count.is_fix() && self.arg_count.get_fix_unchecked() > 0)
+            || (self.arg_count.is_range() && self.arg_count.get_max() != Some(0))
     }
 
     /// Check if the option accepts more than one arguments.
@@ -241,6 +674,7 @@ impl AnpOption {
     pub fn has_args(&self) -> bool {
         self.arg_count.is_unlimited()
             || (self.arg_count.is_fix() && self.arg_count.get_fix_unchecked() > 1)
+            || (self.arg_count.is_range() && matches!(self.arg_count.get_max(), None | Some(2..)))
     }
 
     /// Check if the option has a long option name.
@@ -270,6 +704,62 @@ impl AnpOption {
         self.required
     }
 
+    /// Check whether the option is a property option, see [`OptionBuilder::property_option`].
+    pub fn is_property(&self) -> bool {
+        self.property
+    }
+
+    /// Check whether the option is negatable, see [`OptionBuilder::negatable`].
+    pub fn is_negatable(&self) -> bool {
+        self.negatable
+    }
+
+    /// Check whether the long option is passed with a single hyphen, see
+    /// [`OptionBuilder::single_hyphen_long`].
+    pub fn is_single_hyphen_long(&self) -> bool {
+        self.single_hyphen_long
+    }
+
+    /// Check whether the values are trimmed, see [`OptionBuilder::trim_values`].
+    pub fn trims_values(&self) -> bool {
+        self.trim_values
+    }
+
+    /// Get the token that ends the values, see [`OptionBuilder::stop_token`].
+    pub fn get_stop_token(&self) -> Option<&str> {
+        self.stop_token.as_deref()
+    }
+
+    /// Check whether the option is eager, see [`OptionBuilder::eager`].
+    pub fn is_eager(&self) -> bool {
+        self.eager
+    }
+
+    /// Check whether the option captures all remaining tokens, see
+    /// [`OptionBuilder::trailing_var_arg`].
+    pub fn is_trailing_var_arg(&self) -> bool {
+        self.trailing_var_arg
+    }
+
+    /// Get the kind of value the option takes, see [`OptionBuilder::value_hint`].
+    pub fn get_value_hint(&self) -> Option<ValueHint> {
+        self.value_hint
+    }
+
+    /// Check whether the option is passed in the negated form `--no-<long_option>`.
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Get the negated long option name `no-<long_option>` if the option is negatable.
+    pub fn get_negated_long_opt(&self) -> Option<String> {
+        if self.negatable {
+            self.long_option.as_ref().map(|long_opt| format!("no-{}", long_opt))
+        } else {
+            None
+        }
+    }
+
     pub fn accepts_arg(&self) -> bool {
         if !(self.has_arg() || self.has_args() || self.has_optional_arg()) {
             return false;
@@ -277,7 +767,7 @@ impl AnpOption {
         if self.arg_count.is_uninitialized() {
             return false;
         }
-        if self.arg_count.is_fix() && self.values.len() >= self.arg_count.get_fix_unchecked() {
+        if self.arg_count.get_max().is_some_and(|max| self.values.len() >= max) {
             return false;
         }
         return true;
@@ -290,13 +780,20 @@ impl AnpOption {
         if self.arg_count.is_unlimited() {
             return self.values.is_empty();
         }
+        if self.arg_count.is_range() {
+            return self.values.len() < self.arg_count.get_min();
+        }
         return self.accepts_arg();
     }
 
-    fn add(&mut self, value: String) -> Result<(), OptionErr> {
+    fn add(&mut self, mut value: String) -> Result<(), OptionErr> {
         if !self.accepts_arg() {
             return Err(OptionErr::of(Some(self), "cannot add value, list full"));
         }
+        if self.trim_values {
+            value = value.trim().to_owned();
+        }
+        self.validate_value(&value)?;
         self.values.push(value);
         Ok(())
     }
@@ -305,33 +802,60 @@ impl AnpOption {
         if self.arg_count.is_uninitialized() {
             return Err(OptionErr::of(Some(self), "no arg allowed"));
         }
+        self.raw_values.push(value.to_owned());
         self.process_value(value)
     }
 
     fn process_value(&mut self, mut value: &str) -> Result<(), OptionErr> {
-        if let Some(value_sep) = self.value_sep {
-            let mut index = value.find(value_sep);
+        if let Some(value_sep) = self.value_sep.clone().filter(|sep| !sep.is_empty()) {
+            let mut index = value.find(&value_sep);
 
             while let Some(i) = index {
-                if self.arg_count.is_fix()
-                    && self.values.len() == self.arg_count.get_fix_unchecked() - 1
-                {
+                if self.arg_count.get_max().is_some_and(|max| self.values.len() + 1 >= max) {
                     break;
                 }
 
                 self.add((&value[..i]).to_owned())?;
 
-                value = &value[i + 1..];
+                value = &value[i + value_sep.len()..];
 
-                index = value.find(value_sep);
+                index = value.find(&value_sep);
             }
         }
 
         self.add(value.to_owned())
     }
 
+    fn validate_value(&self, value: &str) -> Result<(), OptionErr> {
+        if let Some((min, max)) = self.int_range {
+            let number = value.parse::<i64>().map_err(|_| {
+                OptionErr::of(Some(self), &format!("value '{}' is not an integer", value))
+            })?;
+            if number < min {
+                return Err(OptionErr::of(Some(self),
+                                         &format!("value '{}' is less than the minimum {}", value, min)));
+            }
+            if number > max {
+                return Err(OptionErr::of(Some(self),
+                                         &format!("value '{}' is greater than the maximum {}", value, max)));
+            }
+        }
+        if let Some(allowed) = &self.allowed_values {
+            if !allowed.iter().any(|v| v == value) {
+                return Err(OptionErr::of(Some(self),
+                                         &format!("value '{}' is not allowed, possible values are {}",
+                                                  value, allowed.join(", "))));
+            }
+        }
+        if let Some(ValueValidator(validator)) = &self.validator {
+            validator(value).map_err(|msg| OptionErr::of(Some(self), &msg))?;
+        }
+        Ok(())
+    }
+
     pub fn clear_values(&mut self) {
         self.values.clear();
+        self.raw_values.clear();
     }
 
     pub fn get_arg_name(&self) -> Option<&String> {
@@ -354,6 +878,11 @@ impl AnpOption {
         }
     }
 
+    /// Get the first character of the key.
+    ///
+    /// Multi-character short options like `-req` and `-res` share the same id, so the id
+    /// can't identify an option. Use [`Self::get_key`] instead.
+    #[deprecated(note = "options with multi-character names may share an id, use `get_key` instead")]
     pub fn get_id(&self) -> char {
         self.get_key().chars().next().unwrap()
     }
@@ -362,6 +891,26 @@ impl AnpOption {
         self.long_option.as_ref()
     }
 
+    /// Get the section name of the option, see [`OptionBuilder::section`].
+    pub fn get_section(&self) -> Option<&String> {
+        self.section.as_ref()
+    }
+
+    /// Get the inclusive integer range of the values, see [`OptionBuilder::int_range`].
+    pub fn get_int_range(&self) -> Option<(i64, i64)> {
+        self.int_range
+    }
+
+    /// Get the default value of the option, see [`OptionBuilder::default_value`].
+    pub fn get_default_value(&self) -> Option<&String> {
+        self.default_value.as_ref()
+    }
+
+    /// Get the allowed values of the option, see [`OptionBuilder::allowed_values`].
+    pub fn get_allowed_values(&self) -> Option<&Vec<String>> {
+        self.allowed_values.as_ref()
+    }
+
     pub fn get_opt(&self) -> Option<&String> {
         self.option.as_ref()
     }
@@ -378,8 +927,26 @@ impl AnpOption {
         self.values.iter().map(|v| T::from_str(v)).collect()
     }
 
+    /// Get the values as they were passed, before splitting by the value separator.
+    ///
+    /// For example, `-Dkey=value` with separator `=` has the values `key` and `value`,
+    /// and the raw value `key=value`. Without a value separator, the raw values are the
+    /// same as the values.
+    pub fn get_raw_values(&self) -> Vec<&str> {
+        self.raw_values.iter().map(|v| v.as_str()).collect()
+    }
+
+    /// Get the value separator if it's a single char, see [`Self::get_value_separator_str`].
     pub fn get_value_separator(&self) -> Option<char> {
-        self.value_sep
+        let mut chars = self.value_sep.as_ref()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+
+    pub fn get_value_separator_str(&self) -> Option<&str> {
+        self.value_sep.as_deref()
     }
 
     pub fn set_arg_name(&mut self, arg_name: &str) {
@@ -407,7 +974,27 @@ impl AnpOption {
     }
 
     pub fn set_value_separator(&mut self, value_sep: char) {
-        self.value_sep = Some(value_sep);
+        self.value_sep = Some(value_sep.to_string());
+    }
+
+    pub fn set_value_separator_str(&mut self, value_sep: &str) {
+        self.value_sep = Some(value_sep.to_owned());
+    }
+
+    pub fn set_section(&mut self, section: &str) {
+        self.section = Some(section.to_owned());
+    }
+
+    pub fn set_negated(&mut self, negated: bool) {
+        self.negated = negated;
+    }
+
+    pub fn set_default_value(&mut self, value: &str) {
+        self.default_value = Some(value.to_owned());
+    }
+
+    pub fn set_int_range(&mut self, min: i64, max: i64) {
+        self.int_range = Some((min, max));
     }
 }
 
@@ -422,7 +1009,22 @@ impl Clone for AnpOption {
             optional_arg: self.optional_arg.clone(),
             arg_count: self.arg_count.clone(),
             value_sep: self.value_sep.clone(),
+            section: self.section.clone(),
+            int_range: self.int_range,
+            allowed_values: self.allowed_values.clone(),
+            validator: self.validator.clone(),
+            default_value: self.default_value.clone(),
+            property: self.property,
+            negatable: self.negatable,
+            negated: self.negated,
+            single_hyphen_long: self.single_hyphen_long,
+            trim_values: self.trim_values,
+            stop_token: self.stop_token.clone(),
+            eager: self.eager,
+            value_hint: self.value_hint,
+            trailing_var_arg: self.trailing_var_arg,
             values: Vec::new(),
+            raw_values: Vec::new(),
         }
     }
 }
@@ -493,6 +1095,7 @@ pub struct OptionGroup {
     option_map: HashMap<String, Rc<RefCell<AnpOption>>>,
     selected: Option<String>,
     required: bool,
+    note: Option<String>,
 }
 
 impl OptionGroup {
@@ -503,6 +1106,7 @@ impl OptionGroup {
             option_map: HashMap::new(),
             selected: None,
             required: false,
+            note: None,
         }
     }
 
@@ -514,6 +1118,30 @@ impl OptionGroup {
         self
     }
 
+    /// Set whether the group is required, see [`Self::set_required`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, OptionGroup};
+    ///
+    /// let group = OptionGroup::new()
+    ///     .required(true)
+    ///     .add_option(AnpOption::builder().option("a").build().unwrap())
+    ///     .add_option(AnpOption::builder().option("b").build().unwrap());
+    /// assert!(group.is_required());
+    /// ```
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Set the note shown after the group in the usage, see [`Self::set_note`].
+    pub fn note(mut self, note: &str) -> Self {
+        self.set_note(note);
+        self
+    }
+
     /// Get the keys of all options in the group.
     /// The key is short option name if exists, otherwise long option name.
     pub fn get_names(&self) -> Vec<&str> {
@@ -525,6 +1153,14 @@ impl OptionGroup {
         self.option_map.values().map(|opt| Rc::clone(opt)).collect()
     }
 
+    /// Remove the option with the `key` from the group, returns the removed option if exists.
+    pub fn remove_option(&mut self, key: &str) -> Option<Rc<RefCell<AnpOption>>> {
+        if self.selected.as_deref() == Some(key) {
+            self.selected = None;
+        }
+        self.option_map.remove(key)
+    }
+
     /// Get selected option key in the group.
     pub fn get_selected(&self) -> Option<&String> {
         self.selected.as_ref()
@@ -540,6 +1176,34 @@ impl OptionGroup {
         self.required = required;
     }
 
+    /// Get the note of the group.
+    pub fn get_note(&self) -> Option<&String> {
+        self.note.as_ref()
+    }
+
+    /// Set the note shown after the group in the usage, like `(-a | -b) choose exactly one`.
+    ///
+    /// The note is wrapped with the rest of the usage.
+    pub fn set_note(&mut self, note: &str) {
+        self.note = Some(note.to_string());
+    }
+
+    /// The members sorted by key like `(-a | --beta)`, for messages naming the group.
+    pub(crate) fn member_names(&self) -> String {
+        let mut options = self.get_options();
+        options.sort_by(|a, b| a.borrow().get_key().cmp(b.borrow().get_key()));
+        let names: Vec<String> = options.iter()
+            .map(|opt| {
+                let opt = opt.borrow();
+                match opt.get_opt() {
+                    Some(opt) => format!("-{}", opt),
+                    None => format!("--{}", opt.get_long_opt().unwrap()),
+                }
+            })
+            .collect();
+        format!("({})", names.join(" | "))
+    }
+
     /// Set the selected key in the group.
     /// This is for internal usage.
     pub fn set_selected(&mut self, option: Option<&AnpOption>) -> Result<(), OptionErr> {
@@ -667,10 +1331,13 @@ impl Display for Required {
 #[derive(Clone)]
 pub struct Options {
     short_opts: HashMap<String, Rc<RefCell<AnpOption>>>,
-    long_opts: HashMap<String, Rc<RefCell<AnpOption>>>,
+    // sorted by name, so the long options matching a prefix are adjacent
+    long_opts: BTreeMap<String, Rc<RefCell<AnpOption>>>,
+    negated_opts: HashMap<String, Rc<RefCell<AnpOption>>>,
     required_opts: Vec<Rc<RefCell<Required>>>,
     option_groups: HashMap<String, Rc<HashRefCellGroup>>,
     defaults: Option<HashMap<String, String>>,
+    key_order: Vec<String>,
 }
 
 impl Options {
@@ -679,10 +1346,12 @@ impl Options {
     pub fn new() -> Options {
         Options {
             short_opts: HashMap::new(),
-            long_opts: HashMap::new(),
+            long_opts: BTreeMap::new(),
+            negated_opts: HashMap::new(),
             required_opts: Vec::new(),
             option_groups: HashMap::new(),
             defaults: None,
+            key_order: Vec::new(),
         }
     }
 
@@ -691,7 +1360,11 @@ impl Options {
         self.defaults.is_some()
     }
 
-    /// Set default values for options.
+    /// Set default values for options, the keys are either short or long option names.
+    ///
+    /// The values here take precedence over the default value of the option,
+    /// see [`OptionBuilder::default_value`] for the accepted values of options without
+    /// argument.
     pub fn set_defaults(&mut self, defaults: HashMap<String, String>) {
         self.defaults = Some(defaults);
     }
@@ -701,8 +1374,23 @@ impl Options {
         self.defaults.as_ref()
     }
 
+    /// Get the default value of the `option`, either from [`Self::set_defaults`] or
+    /// from [`OptionBuilder::default_value`], the former takes precedence.
+    ///
+    /// The default under the key of the option takes precedence over the one under
+    /// its long option.
+    pub fn get_option_default<'a>(&'a self, option: &'a AnpOption) -> Option<&'a String> {
+        self.defaults.as_ref()
+            .and_then(|defaults| defaults.get(option.get_key())
+                .or_else(|| option.get_long_opt().and_then(|long_opt| defaults.get(long_opt))))
+            .or(option.get_default_value())
+    }
+
     /// Add an [`AnpOption`] to the collection.
     ///
+    /// If an option with the same key or long option already exists, it's overwritten.
+    /// Use [`Self::try_add_option`] to detect the duplication.
+    ///
     /// Also see [`Self::add_option0`], [`Self::add_option1`], [`Self::add_option2`],
     /// [`Self::add_required_option`]
     pub fn add_option(&mut self, option: AnpOption) {
@@ -710,12 +1398,256 @@ impl Options {
         self.add_option_inner(option);
     }
 
+    /// Add an [`AnpOption`] to the collection unless it duplicates an existing option.
+    ///
+    /// # Error
+    ///
+    /// Returns an error naming the conflicting key if the key or the long option of
+    /// `option` is already added, or if both `option` and an added option are
+    /// [`OptionBuilder::trailing_var_arg`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, Options};
+    ///
+    /// let mut options = Options::new();
+    /// options.try_add_option(AnpOption::builder().option("f").long_option("file").build().unwrap()).unwrap();
+    /// assert!(options.try_add_option(AnpOption::builder().option("o").long_option("file").build().unwrap()).is_err());
+    /// ```
+    pub fn try_add_option(&mut self, option: AnpOption) -> Result<(), OptionErr> {
+        self.check_duplicate(&option)?;
+        self.add_option(option);
+        Ok(())
+    }
+
+    fn check_duplicate(&self, option: &AnpOption) -> Result<(), OptionErr> {
+        if self.short_opts.contains_key(option.get_key()) {
+            return Err(OptionErr::of(Some(option), &format!("duplicate option '{}'", option.get_key())));
+        }
+        if let Some(long_opt) = option.get_long_opt().filter(|l| self.long_opts.contains_key(*l)) {
+            return Err(OptionErr::of(Some(option), &format!("duplicate long option '{}'", long_opt)));
+        }
+        if option.is_trailing_var_arg() {
+            self.check_trailing_var_arg(Some(option.get_key()))?;
+        }
+        Ok(())
+    }
+
+    /// Check there is no trailing var arg option other than `key`.
+    fn check_trailing_var_arg(&self, key: Option<&str>) -> Result<(), OptionErr> {
+        let mut trailing = self.key_order.iter()
+            .filter(|k| self.short_opts[*k].borrow().is_trailing_var_arg());
+        if let Some(other) = trailing.find(|k| Some(k.as_str()) != key) {
+            return Err(OptionErr::of(None, &format!("option '{}' is already a trailing var arg option", other)));
+        }
+        Ok(())
+    }
+
+    /// Create an `Options` from option definitions in TOML.
+    ///
+    /// Each option is defined in an `[[option]]` table with the keys `short`, `long`,
+    /// `has_arg`, `required`, `desc` and `arg_name`, all optional except that at least
+    /// one of `short` and `long` must be present. Requires the `toml` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::Options;
+    ///
+    /// let options = Options::from_toml_str(r#"
+    /// [[option]]
+    /// short = "f"
+    /// long = "file"
+    /// has_arg = true
+    /// arg_name = "FILE"
+    /// desc = "the input file"
+    /// "#).unwrap();
+    /// assert!(options.has_long_option("file"));
+    /// ```
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the TOML is malformed, a definition has an unknown key or
+    /// a value of the wrong type, the option is invalid, or two options conflict.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Options, OptionErr> {
+        let table: toml::Table = s.parse()
+            .map_err(|e: toml::de::Error| OptionErr::of(None, &format!("invalid toml: {}", e.message())))?;
+
+        let mut options = Options::new();
+        let definitions = match table.get("option") {
+            None => return Ok(options),
+            Some(toml::Value::Array(definitions)) => definitions,
+            Some(_) => return Err(OptionErr::of(None, "'option' must be an array of tables")),
+        };
+
+        for definition in definitions {
+            let definition = definition.as_table()
+                .ok_or_else(|| OptionErr::of(None, "'option' must be an array of tables"))?;
+            let mut builder = AnpOption::builder();
+            for (key, value) in definition {
+                let type_err = |expected: &str| {
+                    OptionErr::of(None, &format!("invalid value for '{}', expected {}", key, expected))
+                };
+                builder = match key.as_str() {
+                    "short" => builder.option(value.as_str().ok_or_else(|| type_err("a string"))?),
+                    "long" => builder.long_option(value.as_str().ok_or_else(|| type_err("a string"))?),
+                    "desc" => builder.desc(value.as_str().ok_or_else(|| type_err("a string"))?),
+                    "arg_name" => builder.arg_name(value.as_str().ok_or_else(|| type_err("a string"))?),
+                    "has_arg" => builder.has_arg(value.as_bool().ok_or_else(|| type_err("a boolean"))?),
+                    "required" => builder.required(value.as_bool().ok_or_else(|| type_err("a boolean"))?),
+                    _ => return Err(OptionErr::of(None, &format!("unknown key '{}' in option definition", key))),
+                };
+            }
+            options.try_add_option(builder.build()?)?;
+        }
+
+        Ok(options)
+    }
+
+    /// Copy all options, option groups and default values from `other` into the collection.
+    ///
+    /// The options are copied in the order they were added to `other`. The members of
+    /// an option group in `other` are copied as a new group with the same members, so
+    /// they stay mutually exclusive, and the group is still required if it was. For the
+    /// default values with the same key, the existing one takes precedence.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if any option in `other` has the same key or long option as an
+    /// existing option, in which case nothing is copied.
+    pub fn merge(&mut self, other: &Options) -> Result<(), OptionErr> {
+        for option in other.get_options_in_order() {
+            self.check_duplicate(&option)?;
+        }
+
+        let mut merged_groups: Vec<Rc<HashRefCellGroup>> = vec![];
+        for option in other.get_options_in_order() {
+            match other.get_option_group(&option) {
+                Some(group) => {
+                    if merged_groups.contains(&group) {
+                        continue;
+                    }
+                    let mut new_group = OptionGroup::new().required(group.borrow().is_required());
+                    new_group.note = group.borrow().note.clone();
+                    for member in group.borrow().get_options() {
+                        new_group = new_group.add_option(member.borrow().clone());
+                    }
+                    self.add_option_group(new_group);
+                    merged_groups.push(group);
+                }
+                None => self.add_option(option.clone()),
+            }
+        }
+
+        if let Some(other_defaults) = other.get_defaults() {
+            let defaults = self.defaults.get_or_insert_with(HashMap::new);
+            for (key, value) in other_defaults {
+                defaults.entry(key.to_owned()).or_insert_with(|| value.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the consistency of the collection, which is otherwise only found when parsing,
+    /// or never.
+    ///
+    /// # Error
+    ///
+    /// Returns an error describing the first problem found, which is one of:
+    /// - a default value or a required option references no option.
+    /// - a member of an option group is not in the collection, or is in multiple groups.
+    /// - a required option is a member of an option group.
+    /// - a long option or a negated long option conflicts with the name of another option.
+    /// - more than one option is a trailing var arg option.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use anpcli::Options;
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("f", "file", true, "").unwrap();
+    /// assert!(options.validate().is_ok());
+    ///
+    /// options.set_defaults(HashMap::from([("o".to_string(), "out.txt".to_string())]));
+    /// assert!(options.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), OptionErr> {
+        if let Some(defaults) = &self.defaults {
+            let mut keys: Vec<&String> = defaults.keys().collect();
+            keys.sort();
+            for key in keys {
+                if self.get_option(key).is_none() {
+                    return Err(OptionErr::of(None, &format!("default value references undefined option '{}'", key)));
+                }
+            }
+        }
+
+        let mut groups: Vec<Rc<HashRefCellGroup>> = vec![];
+        for required in self.required_opts.iter() {
+            match &*required.borrow() {
+                Required::OptKey(key) => {
+                    if !self.short_opts.contains_key(key) {
+                        return Err(OptionErr::of(None, &format!("required option '{}' is undefined", key)));
+                    }
+                    if self.option_groups.contains_key(key) {
+                        return Err(OptionErr::of(None, &format!("required option '{}' is a member of an option group", key)));
+                    }
+                }
+                Required::OptGroup(group) => groups.push(Rc::clone(group)),
+            }
+        }
+        for key in self.key_order.iter() {
+            if let Some(group) = self.option_groups.get(key) {
+                if !groups.iter().any(|g| Rc::ptr_eq(g, group)) {
+                    groups.push(Rc::clone(group));
+                }
+            }
+        }
+
+        for group in groups.iter() {
+            let mut names = group.borrow().get_names().into_iter().map(|n| n.to_owned()).collect::<Vec<_>>();
+            names.sort();
+            for name in names {
+                if !self.short_opts.contains_key(&name) {
+                    return Err(OptionErr::of(None, &format!("option group member '{}' is undefined", name)));
+                }
+                if !self.option_groups.get(&name).is_some_and(|g| Rc::ptr_eq(g, group)) {
+                    return Err(OptionErr::of(None, &format!("option '{}' is in multiple option groups", name)));
+                }
+            }
+        }
+
+        for (long_opt, option) in self.long_opts.iter() {
+            if self.short_opts.get(long_opt).is_some_and(|o| !Rc::ptr_eq(o, option)) {
+                return Err(OptionErr::of(Some(&option.borrow()),
+                                         &format!("long option '{}' conflicts with option '{}'", long_opt, long_opt)));
+            }
+        }
+        for negated in self.negated_opts.keys() {
+            if self.long_opts.contains_key(negated) {
+                return Err(OptionErr::of(None, &format!("negated option '{}' conflicts with long option '{}'", negated, negated)));
+            }
+        }
+        if let Some(first) = self.key_order.iter().find(|k| self.short_opts[*k].borrow().is_trailing_var_arg()) {
+            self.check_trailing_var_arg(Some(first))?;
+        }
+        Ok(())
+    }
+
     fn add_option_inner(&mut self, option: Rc<RefCell<AnpOption>>) {
         if let Some(long_opt) = option.borrow().get_long_opt() {
             self.long_opts
                 .insert(long_opt.to_owned(), Rc::clone(&option));
         }
 
+        if let Some(negated_long_opt) = option.borrow().get_negated_long_opt() {
+            self.negated_opts.insert(negated_long_opt, Rc::clone(&option));
+        }
+
         if option.borrow().is_required() {
             let index = self
                 .required_opts
@@ -729,6 +1661,9 @@ impl Options {
         }
 
         let key = option.borrow().get_key().to_owned();
+        if !self.short_opts.contains_key(&key) {
+            self.key_order.push(key.to_owned());
+        }
         self.short_opts.insert(key, option);
     }
 
@@ -790,7 +1725,11 @@ impl Options {
                 .push(Rc::new(RefCell::new(Required::OptGroup(Rc::clone(&group)))));
         }
 
-        for option in group.borrow().get_options() {
+        // the group members are kept in a map, sort them to keep a stable declaration order
+        let mut members = group.borrow().get_options();
+        members.sort_by(|a, b| a.borrow().get_key().cmp(b.borrow().get_key()));
+
+        for option in members {
             option.borrow_mut().set_required(false);
             self.add_option_inner(Rc::clone(&option));
 
@@ -821,6 +1760,84 @@ impl Options {
         Ok(())
     }
 
+    /// Remove the option by its short or long option name, returns the removed option if exists.
+    ///
+    /// The option is also removed from the required options, its option group and the
+    /// default values. If the group becomes empty, the group is removed as well.
+    ///
+    /// The group is replaced rather than changed in place, so clones of this `Options`
+    /// sharing the group are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::Options;
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("f", "file", true, "the input file").unwrap();
+    /// assert!(options.remove_option("file").is_some());
+    /// assert!(!options.has_option("f"));
+    /// assert!(options.remove_option("f").is_none());
+    /// ```
+    pub fn remove_option(&mut self, key: &str) -> Option<AnpOption> {
+        let option = self.get_option(key)?;
+        let option = option.borrow();
+        let key = option.get_key().to_owned();
+
+        self.short_opts.remove(&key);
+        if let Some(long_opt) = option.get_long_opt() {
+            self.long_opts.remove(long_opt);
+        }
+        if let Some(negated_long_opt) = option.get_negated_long_opt() {
+            self.negated_opts.remove(&negated_long_opt);
+        }
+        self.key_order.retain(|k| k != &key);
+        self.required_opts.retain(|r| r.borrow().deref() != &Required::OptKey(key.to_owned()));
+
+        if let Some(defaults) = self.defaults.as_mut() {
+            defaults.retain(|k, _| Some(k) != option.get_opt() && Some(k) != option.get_long_opt());
+        }
+
+        if let Some(group) = self.option_groups.remove(&key) {
+            let mut new_group = {
+                let group = group.borrow();
+                OptionGroup {
+                    option_map: group.option_map.clone(),
+                    selected: group.selected.clone(),
+                    required: group.required,
+                    note: group.note.clone(),
+                }
+            };
+            new_group.remove_option(&key);
+            let empty = new_group.get_options().is_empty();
+            let new_group = Rc::new(HashRefCellGroup(RefCell::new(new_group)));
+
+            for g in self.option_groups.values_mut().filter(|g| Rc::ptr_eq(g, &group)) {
+                *g = Rc::clone(&new_group);
+            }
+            self.required_opts = self.required_opts.iter()
+                .filter_map(|r| match r.borrow().deref() {
+                    Required::OptGroup(g) if Rc::ptr_eq(g, &group) => (!empty)
+                        .then(|| Rc::new(RefCell::new(Required::OptGroup(Rc::clone(&new_group))))),
+                    _ => Some(Rc::clone(r)),
+                })
+                .collect();
+        }
+
+        Some(option.clone())
+    }
+
+    /// Remove all options, option groups and default values.
+    pub fn clear(&mut self) {
+        self.short_opts.clear();
+        self.long_opts.clear();
+        self.negated_opts.clear();
+        self.required_opts.clear();
+        self.option_groups.clear();
+        self.defaults = None;
+        self.key_order.clear();
+    }
+
     /// For internal usage.
     pub fn get_matching_options(&self, opt: &str) -> Vec<String> {
         let opt = Util::strip_leading_hyphens(opt);
@@ -829,14 +1846,10 @@ impl Options {
             return vec![opt.to_owned()];
         }
 
-        let mut matching_opts = Vec::new();
-        for (key, _) in self.long_opts.iter() {
-            if key.starts_with(opt) {
-                matching_opts.push(key.to_owned());
-            }
-        }
-
-        return matching_opts;
+        self.long_opts.range::<str, _>((Bound::Included(opt), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(opt))
+            .map(|(key, _)| key.to_owned())
+            .collect()
     }
 
     pub fn get_option(&self, opt: &str) -> Option<Rc<RefCell<AnpOption>>> {
@@ -851,6 +1864,20 @@ impl Options {
         }
     }
 
+    /// Get the option by its long option name only.
+    pub fn get_long_option(&self, long_opt: &str) -> Option<Rc<RefCell<AnpOption>>> {
+        let long_opt = Util::strip_leading_hyphens(long_opt);
+        self.long_opts.get(long_opt).map(Rc::clone)
+    }
+
+    /// Get the negatable option by its negated long option name, like `no-color`.
+    ///
+    /// Also see [`OptionBuilder::negatable`].
+    pub fn get_negated_option(&self, opt: &str) -> Option<Rc<RefCell<AnpOption>>> {
+        let opt = Util::strip_leading_hyphens(opt);
+        self.negated_opts.get(opt).map(Rc::clone)
+    }
+
     pub fn get_option_group(&self, option: &AnpOption) -> Option<Rc<HashRefCellGroup>> {
         if let Some(opt_group) = self.option_groups.get(option.get_key()) {
             Some(Rc::clone(opt_group))
@@ -867,6 +1894,65 @@ impl Options {
         self.short_opts.values().map(|x| x.borrow()).collect()
     }
 
+    /// Get the number of options in the collection.
+    pub fn len(&self) -> usize {
+        self.short_opts.len()
+    }
+
+    /// Check if there is no option in the collection.
+    pub fn is_empty(&self) -> bool {
+        self.short_opts.is_empty()
+    }
+
+    /// Get all options in the order they were added to the collection.
+    ///
+    /// Replacing an option with the same key keeps its original position.
+    pub fn get_options_in_order(&self) -> Vec<Ref<'_, AnpOption>> {
+        self.key_order.iter()
+            .filter_map(|key| self.short_opts.get(key))
+            .map(|x| x.borrow())
+            .collect()
+    }
+
+    /// Get the short names of all options in the order they were added, options
+    /// without a short name are skipped.
+    pub fn option_keys(&self) -> Vec<String> {
+        self.get_options_in_order().iter()
+            .filter_map(|option| option.get_opt().cloned())
+            .collect()
+    }
+
+    /// Get the long names of all options in the order they were added, options
+    /// without a long name are skipped.
+    pub fn long_option_keys(&self) -> Vec<String> {
+        self.get_options_in_order().iter()
+            .filter_map(|option| option.get_long_opt().cloned())
+            .collect()
+    }
+
+    /// Get the short and long names of all options in the order they were added,
+    /// the short name of an option comes before its long name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, Options};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option2("v", "verbose", false, "print verbosely").unwrap();
+    /// options.add_option(AnpOption::builder().long_option("color").build().unwrap());
+    /// options.add_option0("q", false, "print quietly").unwrap();
+    ///
+    /// assert_eq!(vec!["v", "q"], options.option_keys());
+    /// assert_eq!(vec!["verbose", "color"], options.long_option_keys());
+    /// assert_eq!(vec!["v", "verbose", "color", "q"], options.all_keys());
+    /// ```
+    pub fn all_keys(&self) -> Vec<String> {
+        self.get_options_in_order().iter()
+            .flat_map(|option| option.get_opt().into_iter().chain(option.get_long_opt()).cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
     pub fn get_required_options(&self) -> Vec<Rc<RefCell<Required>>> {
         self.required_opts.iter().map(|r| Rc::clone(r)).collect()
     }
@@ -919,3 +2005,204 @@ impl Deref for HashRefCellGroup {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    use crate::option::{AnpOption, OptionGroup, Options, Required};
+    use crate::parser::{DefaultParser, Parser};
+
+    fn defaults(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut options = Options::new();
+        options.add_option2("f", "file", true, "").unwrap();
+        options.set_defaults(defaults(&[("f", "a.txt")]));
+
+        let mut other = Options::new();
+        other.add_required_option("o", "output", true, "").unwrap();
+        let group = OptionGroup::new()
+            .required(true)
+            .add_option(AnpOption::builder().option("x").build().unwrap())
+            .add_option(AnpOption::builder().option("y").build().unwrap());
+        other.add_option_group(group);
+        other.set_defaults(defaults(&[("f", "b.txt"), ("o", "out.txt")]));
+
+        options.merge(&other).unwrap();
+
+        let keys: Vec<String> = options.get_options_in_order().iter().map(|o| o.get_key().to_owned()).collect();
+        assert_eq!(vec!["f", "o", "x", "y"], keys);
+        assert_eq!(2, options.get_required_options().len());
+        let x = options.get_option("x").unwrap();
+        let y = options.get_option("y").unwrap();
+        let group = options.get_option_group(&x.borrow()).unwrap();
+        assert!(group.borrow().is_required());
+        assert_eq!(Some(&group), options.get_option_group(&y.borrow()).as_ref());
+        assert_eq!(Some(&defaults(&[("f", "a.txt"), ("o", "out.txt")])), options.get_defaults());
+
+        // the groups of `other` are not shared
+        assert!(other.get_option_groups().iter().all(|g| !Rc::ptr_eq(g, &group)));
+    }
+
+    #[test]
+    fn test_trim_values() {
+        for (trim, expected) in [(true, "bob"), (false, " bob ")] {
+            let mut options = Options::new();
+            options.add_option(AnpOption::builder().long_option("name").has_arg(true).trim_values(trim).build().unwrap());
+
+            let cmd = DefaultParser::builder().build().parse_args(&options, &["--name", " bob "]).unwrap();
+            assert_eq!(Some(expected.to_string()), cmd.get_value::<String>("name").map(|v| v.unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_int_range_with_value_separator() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("n").has_args().value_separator(',').int_range(1, 10).build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        let cmd = parser.parse_args(&options, &["-n", "1,5,10"]).unwrap();
+        assert_eq!(vec![1, 5, 10], cmd.get_expected_values::<i64>("n"));
+        let err = parser.parse_args(&options, &["-n", "1,50,10"]).unwrap_err();
+        assert!(err.to_string().contains("'50' is greater than the maximum 10"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let group = |a: &str, b: &str| OptionGroup::new()
+            .add_option(AnpOption::builder().option(a).build().unwrap())
+            .add_option(AnpOption::builder().option(b).build().unwrap());
+
+        let mut options = Options::new();
+        options.add_option_group(group("a", "b"));
+        options.add_option2("v", "verbose", false, "").unwrap();
+        assert_eq!(Ok(()), options.validate());
+
+        options.add_option_group(group("b", "c"));
+        assert!(options.validate().unwrap_err().to_string().contains("option 'b' is in multiple option groups"));
+
+        let mut options = Options::new();
+        options.add_option_group(group("a", "b"));
+        options.add_required_option("a", "all", false, "").unwrap();
+        assert!(options.validate().unwrap_err().to_string().contains("required option 'a' is a member of an option group"));
+
+        let mut options = Options::new();
+        options.add_option2("v", "verbose", false, "").unwrap();
+        options.add_option(AnpOption::builder().option("x").long_option("v").build().unwrap());
+        assert!(options.validate().unwrap_err().to_string().contains("long option 'v' conflicts with option 'v'"));
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let mut options = Options::new();
+        options.add_option2("f", "file", true, "").unwrap();
+
+        let mut other = Options::new();
+        other.add_option2("o", "output", true, "").unwrap();
+        other.add_option2("g", "file", true, "").unwrap();
+        assert!(options.merge(&other).is_err());
+        assert!(!options.has_option("o"));
+
+        let mut other = Options::new();
+        other.add_option1("f", "").unwrap();
+        assert!(options.merge(&other).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn test_from_toml_str_err() {
+        assert!(Options::from_toml_str("[[option]]\nshort = ").is_err());
+        assert!(Options::from_toml_str("[[option]]\nshort = 1").is_err());
+        assert!(Options::from_toml_str("[[option]]\nshort = \"f\"\nname = \"x\"").is_err());
+        assert!(Options::from_toml_str("[[option]]\ndesc = \"no name\"").is_err());
+        assert!(Options::from_toml_str("[[option]]\nlong = \"f\"\n[[option]]\nlong = \"f\"").is_err());
+
+        let options = Options::from_toml_str("[[option]]\nshort = \"f\"\nrequired = true").unwrap();
+        assert_eq!(1, options.get_required_options().len());
+    }
+
+    #[test]
+    fn test_option_default() {
+        let mut options = Options::new();
+        options.add_option2("f", "file", true, "").unwrap();
+        options.add_option(AnpOption::builder().long_option("level").has_arg(true).default_value("info").build().unwrap());
+        options.set_defaults(defaults(&[("file", "b.txt"), ("f", "a.txt")]));
+        let file = options.get_option("f").unwrap().borrow().clone();
+        let level = options.get_option("level").unwrap().borrow().clone();
+        assert_eq!(Some(&"a.txt".to_string()), options.get_option_default(&file));
+        assert_eq!(Some(&"info".to_string()), options.get_option_default(&level));
+
+        options.set_defaults(defaults(&[("file", "b.txt"), ("level", "debug")]));
+        assert_eq!(Some(&"b.txt".to_string()), options.get_option_default(&file));
+        assert_eq!(Some(&"debug".to_string()), options.get_option_default(&level));
+    }
+
+    #[test]
+    fn test_remove_option_shared_group() {
+        let mut options = Options::new();
+        options.add_option_group(OptionGroup::new()
+            .required(true)
+            .add_option(AnpOption::builder().option("a").build().unwrap())
+            .add_option(AnpOption::builder().option("b").build().unwrap()));
+        let cloned = options.clone();
+
+        options.remove_option("a");
+        let group = options.get_option_group(&options.get_option("b").unwrap().borrow()).unwrap();
+        assert_eq!(vec!["b"], group.borrow().get_names());
+        assert_eq!(Required::OptGroup(group), *options.get_required_options()[0].borrow());
+
+        let mut names = cloned.get_option_group(&cloned.get_option("b").unwrap().borrow()).unwrap().borrow()
+            .get_names().into_iter().map(String::from).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(vec!["a", "b"], names);
+
+        options.remove_option("b");
+        assert!(options.get_required_options().is_empty());
+        assert_eq!(1, cloned.get_required_options().len());
+    }
+
+    #[test]
+    fn test_len() {
+        let mut options = Options::new();
+        assert_eq!(0, options.len());
+        assert!(options.is_empty());
+
+        options.add_option2("f", "file", true, "").unwrap();
+        options.add_option(AnpOption::builder().long_option("verbose").build().unwrap());
+        options.add_option2("f", "force", false, "").unwrap();
+        assert_eq!(2, options.len());
+        assert!(!options.is_empty());
+    }
+
+    #[test]
+    fn test_option_keys() {
+        let mut options = Options::new();
+        options.add_option2("o", "output", true, "").unwrap();
+        options.add_option(AnpOption::builder().long_option("dry-run").negatable(true).build().unwrap());
+        options.add_option0("x", false, "").unwrap();
+        options.add_option2("o", "out", true, "").unwrap();
+
+        assert_eq!(vec!["o", "x"], options.option_keys());
+        assert_eq!(vec!["out", "dry-run"], options.long_option_keys());
+        assert_eq!(vec!["o", "out", "dry-run", "x"], options.all_keys());
+    }
+
+    #[test]
+    fn test_multi_char_short_options() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("req").build().unwrap());
+        options.add_option(AnpOption::builder().option("res").has_arg(true).build().unwrap());
+        assert_eq!(2, options.get_options().len());
+        assert_eq!("req", options.get_option("req").unwrap().borrow().get_key());
+        assert_eq!("res", options.get_option("res").unwrap().borrow().get_key());
+
+        let cmd = DefaultParser::builder().build().parse_args(&options, &["-res", "x"]).unwrap();
+        assert!(!cmd.has_option("req"));
+        assert_eq!(Some("x".to_string()), cmd.get_value::<String>("res").map(|v| v.unwrap()));
+    }
+}
@@ -1,16 +1,20 @@
 use std::cell::RefCell;
-use std::env;
+use std::{env, fs, io};
+use std::ffi::OsStr;
 use std::io::stdout;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::process::exit;
 use std::rc::Rc;
 
-use crate::cmd::CommandLine;
-use crate::error::ParseErr;
+use crate::cmd::{CommandLine, ValueSource};
+use crate::error::{OptionErr, ParseErr, TokenPosition};
 use crate::format::HelpFormatter;
-use crate::option::{AnpOption, Options, Required};
+use crate::option::{AnpOption, ArgCount, HashRefCellGroup, Options, Required, ValueHint};
 use crate::util::Util;
 
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
 /// The parser trait to parse command line arguments.
 pub trait Parser {
 
@@ -21,6 +25,9 @@ pub trait Parser {
     /// If the arguments retrieved from `env::args()` don't meet the requirement of `options`,
     /// [`ParseErr`] is returned.
     ///
+    /// The program exits with code 0 after printing the help or version if requested,
+    /// see [`ParserBuilder::set_auto_help`].
+    ///
     /// Also see [`Self::parse_args`], [`Self::parse_or_exit`]
     fn parse(&mut self, options: &Options) -> Result<CommandLine, ParseErr>;
 
@@ -29,7 +36,11 @@ pub trait Parser {
     /// # Error
     ///
     /// If the arguments retrieved from `env::args()` don't meet the requirement of `options`,
-    /// error message and option help will be print to stderr before exit.
+    /// error message and option help will be print to stderr before exit with code 1, or
+    /// the code set by [`ParserBuilder::set_usage_exit_code`].
+    ///
+    /// The program exits with code 0 after printing the help or version if requested,
+    /// see [`ParserBuilder::set_auto_help`].
     ///
     /// Also see [`Self::parse_args`], [`Self::parse`]
     fn parse_or_exit(&mut self, options: &Options, formatter: &HelpFormatter) -> CommandLine;
@@ -41,42 +52,180 @@ pub trait Parser {
     /// If the arguments retrieved from `env::args()` don't meet the requirement of `options`,
     /// [`ParseErr`] is returned.
     ///
-    /// Also see [`Self::parse_or_exit`], [`Self::parse`]
+    /// Also see [`Self::parse_or_exit`], [`Self::parse`], [`Self::parse_iter`]
     fn parse_args<T: ToString>(&mut self, options: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr>;
+
+    /// Parse the `arguments` from any iterator with provided `options`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::env;
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let options = Options::new();
+    /// let cmd = DefaultParser::builder().build().parse_iter(&options, env::args().skip(1)).unwrap();
+    /// ```
+    ///
+    /// # Error
+    ///
+    /// If the `arguments` don't meet the requirement of `options`, [`ParseErr`] is returned.
+    ///
+    /// The default implementation collects the arguments and passes them to [`Self::parse_args`].
+    ///
+    /// Also see [`Self::parse_args`]
+    fn parse_iter<I, S>(&mut self, options: &Options, arguments: I) -> Result<CommandLine, ParseErr>
+        where I: IntoIterator<Item=S>, S: ToString {
+        let arguments: Vec<String> = arguments.into_iter().map(|a| a.to_string()).collect();
+        self.parse_args(options, &arguments)
+    }
+
+    /// Parse arguments from `env::args_os()` with provided `options`.
+    ///
+    /// Unlike [`Self::parse`], it doesn't panic on arguments which are not valid UTF-8.
+    ///
+    /// # Error
+    ///
+    /// If an argument is not valid UTF-8 and the parser is not lossy,
+    /// [`ParseErr::InvalidUtf8Argument`] is returned.
+    ///
+    /// The default implementation converts the arguments including the program name
+    /// and passes them to [`Self::parse_args`].
+    ///
+    /// Also see [`Self::parse_args_os`], [`Self::parse`]
+    fn parse_os(&mut self, options: &Options) -> Result<CommandLine, ParseErr> {
+        let arguments: Vec<_> = env::args_os().collect();
+        self.parse_args(options, &to_utf8_args(&arguments)?)
+    }
+
+    /// Parse `arguments` which may not be valid UTF-8 with provided `options`.
+    ///
+    /// The `arguments` may be owned like `&[OsString]` or borrowed like `&[&OsStr]`,
+    /// see also [`Self::parse_args_os_slice`].
+    ///
+    /// # Error
+    ///
+    /// If an argument is not valid UTF-8 and the parser is not lossy,
+    /// [`ParseErr::InvalidUtf8Argument`] is returned.
+    ///
+    /// The default implementation converts the arguments strictly and passes them to
+    /// [`Self::parse_args`].
+    ///
+    /// Also see [`Self::parse_os`], [`Self::parse_args`]
+    fn parse_args_os<T: AsRef<OsStr>>(&mut self, options: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr> {
+        self.parse_args(options, &to_utf8_args(arguments)?)
+    }
+
+    /// Parse the borrowed `arguments` with provided `options`, for embedders holding
+    /// `&OsStr` slices like from an FFI boundary.
+    ///
+    /// The conversion is lossy or strict as configured, see [`ParserBuilder::set_os_lossy`].
+    /// Each argument is still copied once into an owned `String`, even if it's valid
+    /// UTF-8, as the [`CommandLine`] owns the parsed values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use anpcli::{DefaultParser, Options, Parser};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option0("f", true, "input file").unwrap();
+    ///
+    /// let cmd = DefaultParser::builder().build()
+    ///     .parse_args_os_slice(&options, &[OsStr::new("-f"), OsStr::new("a.txt")]).unwrap();
+    /// assert_eq!(Some("a.txt".to_string()), cmd.get_value::<String>("f").map(|v| v.unwrap()));
+    /// ```
+    ///
+    /// # Error
+    ///
+    /// If an argument is not valid UTF-8 and the parser is not lossy,
+    /// [`ParseErr::InvalidUtf8Argument`] is returned.
+    ///
+    /// Also see [`Self::parse_args_os`]
+    fn parse_args_os_slice(&mut self, options: &Options, arguments: &[&OsStr]) -> Result<CommandLine, ParseErr> {
+        self.parse_args_os(options, arguments)
+    }
+}
+
+/// Convert the `arguments` to `String`, failing on the first one which is not valid UTF-8.
+fn to_utf8_args<T: AsRef<OsStr>>(arguments: &[T]) -> Result<Vec<String>, ParseErr> {
+    arguments.iter()
+        .enumerate()
+        .map(|(index, argument)| argument.as_ref().to_str()
+            .map(|a| a.to_owned())
+            .ok_or(ParseErr::InvalidUtf8Argument(index)))
+        .collect()
 }
 
+/// A check whether the path exists for the [`ValueHint`], see [`ParserBuilder::set_path_checker`].
+type PathChecker = dyn Fn(&Path, ValueHint) -> bool;
+
 /// The default implementation of [`Parser`] trait.
 pub struct DefaultParser {
-    cmd: Option<CommandLine>,
-    options: Option<Options>,
     stop_at_non_option: bool,
-    current_token: Option<String>,
-    current_option: Option<Rc<RefCell<AnpOption>>>,
-    skip_parsing: bool,
-    expected_opts: Option<Vec<Rc<RefCell<Required>>>>,
     allow_partial_matching: bool,
     strip_leading_and_trailing_quotes: Option<bool>,
+    quote_chars: Vec<char>,
+    skip_program_name: bool,
+    expand_at_files: bool,
+    os_lossy: bool,
+    collect_unknown: bool,
+    allow_negative_numbers: bool,
+    allow_duplicate_options: bool,
+    env_prefix: Option<String>,
+    auto_help: bool,
+    help_aliases: Vec<String>,
+    version: Option<String>,
+    help_formatter: Option<HelpFormatter>,
+    usage_exit_code: i32,
+    validate_paths: bool,
+    path_checker: Option<Box<PathChecker>>,
 }
 
 /// A builder struct to create [`DefaultParser`].
 pub struct ParserBuilder {
     allow_partial_matching: bool,
     strip_leading_and_trailing_quotes: Option<bool>,
+    quote_chars: Vec<char>,
     stop_at_non_option: bool,
+    skip_program_name: bool,
+    expand_at_files: bool,
+    os_lossy: bool,
+    collect_unknown: bool,
+    allow_negative_numbers: bool,
+    allow_duplicate_options: bool,
+    env_prefix: Option<String>,
+    auto_help: bool,
+    help_aliases: Vec<String>,
+    version: Option<String>,
+    help_formatter: Option<HelpFormatter>,
+    usage_exit_code: i32,
+    validate_paths: bool,
+    path_checker: Option<Box<PathChecker>>,
 }
 
 impl ParserBuilder {
     pub fn build(self) -> DefaultParser {
         DefaultParser {
-            cmd: None,
-            options: None,
             stop_at_non_option: self.stop_at_non_option,
-            current_token: None,
-            current_option: None,
-            skip_parsing: false,
-            expected_opts: None,
             allow_partial_matching: self.allow_partial_matching,
             strip_leading_and_trailing_quotes: self.strip_leading_and_trailing_quotes,
+            quote_chars: self.quote_chars,
+            skip_program_name: self.skip_program_name,
+            expand_at_files: self.expand_at_files,
+            os_lossy: self.os_lossy,
+            collect_unknown: self.collect_unknown,
+            allow_negative_numbers: self.allow_negative_numbers,
+            allow_duplicate_options: self.allow_duplicate_options,
+            env_prefix: self.env_prefix,
+            auto_help: self.auto_help,
+            help_aliases: self.help_aliases,
+            version: self.version,
+            help_formatter: self.help_formatter,
+            usage_exit_code: self.usage_exit_code,
+            validate_paths: self.validate_paths,
+            path_checker: self.path_checker,
         }
     }
 
@@ -92,13 +241,203 @@ impl ParserBuilder {
         self
     }
 
+    /// Set the quote characters to strip from option values, which defaults to `"` only.
+    ///
+    /// For example, `&['"', '\'']` strips both double and single quotes. A value is only
+    /// stripped if it starts and ends with the same quote which doesn't appear inside.
+    pub fn set_quote_chars(mut self, quote_chars: &[char]) -> Self {
+        self.quote_chars = quote_chars.to_vec();
+        self
+    }
+
     /// Set whether stop parsing options and consider all remain arguments as arguments.
     ///
-    /// If set to `true`, make sure the executable name is not passed to the parser.
+    /// If set to `true`, parsing stops at the first token that is not an option, which is
+    /// either a bare argument or an unrecognized option. No error is raised for the tokens
+    /// after the stop point, even if they look like options. Regardless of this setting,
+    /// the tokens after `--` are always arguments, the `--` itself is not an argument
+    /// unless it appears after the stop point.
+    ///
+    /// If set to `true` and the arguments passed to [`Parser::parse_args`] start with
+    /// the executable name, make sure [`Self::set_skip_program_name`] is also enabled.
     pub fn set_stop_at_non_option(mut self, stop_at_non_option: bool) -> Self {
         self.stop_at_non_option = stop_at_non_option;
         self
     }
+
+    /// Set whether the first argument passed to [`Parser::parse_args`] is the program name.
+    ///
+    /// If set to `true`, the first argument is not parsed and is available from
+    /// [`CommandLine::get_program_name`]. The arguments from `env::args()` always start
+    /// with the program name, so [`Parser::parse`] ignores this setting.
+    pub fn set_skip_program_name(mut self, skip_program_name: bool) -> Self {
+        self.skip_program_name = skip_program_name;
+        self
+    }
+
+    /// Set whether expand the argument files.
+    ///
+    /// If set to `true`, an argument `@path` is replaced by the arguments read from the
+    /// file `path` before parsing. The arguments in the file are separated by whitespaces,
+    /// use double quotes for an argument containing whitespaces. Argument files can
+    /// reference other argument files, but not recursively.
+    pub fn set_expand_at_files(mut self, expand_at_files: bool) -> Self {
+        self.expand_at_files = expand_at_files;
+        self
+    }
+
+    /// Set whether convert arguments which are not valid UTF-8 lossily.
+    ///
+    /// If set to `true`, [`Parser::parse_os`] and [`Parser::parse_args_os`] replace invalid
+    /// sequences with `U+FFFD`, otherwise [`ParseErr::InvalidUtf8Argument`] is returned.
+    pub fn set_os_lossy(mut self, os_lossy: bool) -> Self {
+        self.os_lossy = os_lossy;
+        self
+    }
+
+    /// Set whether collect unrecognized options instead of raising an error.
+    ///
+    /// If set to `true`, the unrecognized options like `-x` or `--xyz` are available from
+    /// [`CommandLine::get_unknown_options`], and the parsing goes on. It takes precedence
+    /// over [`Self::set_stop_at_non_option`], so parsing doesn't stop at an unrecognized
+    /// option, but still stops at the first bare argument.
+    pub fn set_collect_unknown(mut self, collect_unknown: bool) -> Self {
+        self.collect_unknown = collect_unknown;
+        self
+    }
+
+    /// Set whether the negative numbers like `-5` or `-.5` are positional arguments.
+    ///
+    /// A negative number, either an integer, a float or a hex number like `-0x10`, is
+    /// always accepted as the value of an option that takes an argument. If set to
+    /// `true`, it's also an argument elsewhere, instead of an unrecognized option.
+    pub fn set_allow_negative_numbers(mut self, allow_negative_numbers: bool) -> Self {
+        self.allow_negative_numbers = allow_negative_numbers;
+        self
+    }
+
+    /// Set whether allow an option taking a bounded number of values to be passed more
+    /// than once, in which case the values are collected and [`CommandLine::get_last_value`]
+    /// gets the last one. It's `true` by default.
+    ///
+    /// If set to `false`, the second occurrence of such an option raises
+    /// [`ParseErr::DuplicateOption`]. Options taking unlimited values, like
+    /// [`crate::OptionBuilder::has_args`] or a range without max, property options
+    /// like `-Dkey=value` and flags like `-v` are not affected.
+    pub fn set_allow_duplicate_options(mut self, allow: bool) -> Self {
+        self.allow_duplicate_options = allow;
+        self
+    }
+
+    /// Set the prefix of the environment variables to read the options from.
+    ///
+    /// If set, an option with a long name which is not passed in command line is read
+    /// from the environment variable `<prefix>_<NAME>`, where `NAME` is the long option
+    /// name in upper case with `-` replaced by `_`. For example, `--max-depth` is read from
+    /// `MYAPP_MAX_DEPTH` with prefix `MYAPP`. The environment variable takes precedence
+    /// over the default value, and for an option without argument, it's a flag like
+    /// the default value, see [`crate::OptionBuilder::default_value`].
+    pub fn set_env_prefix(mut self, prefix: &str) -> Self {
+        self.env_prefix = Some(prefix.to_owned());
+        self
+    }
+
+    /// Set whether handle the option with long name `help` automatically.
+    ///
+    /// If set to `true` and the help option is passed, [`Parser::parse`], [`Parser::parse_os`]
+    /// and [`Parser::parse_or_exit`] print the help to stdout and exit with code 0, while
+    /// the other methods return [`ParseErr::HelpRequested`]. The help option still needs
+    /// to be added to the [`Options`], and it takes precedence over the other errors
+    /// like missing required options.
+    ///
+    /// The help is printed by the formatter passed to [`Parser::parse_or_exit`], or the
+    /// one set by [`Self::set_help_formatter`], or a default one.
+    ///
+    /// With partial matching, an abbreviation like `--he` requests the help only if it
+    /// matches the help option unambiguously, otherwise [`ParseErr::AmbiguousOption`] is
+    /// returned as usual. Use [`Self::set_help_aliases`] to accept exact spellings only.
+    pub fn set_auto_help(mut self, auto_help: bool) -> Self {
+        self.auto_help = auto_help;
+        self
+    }
+
+    /// Set the exact spellings requesting the help when [`Self::set_auto_help`] is enabled,
+    /// like `-h`, `--help` and `-?`.
+    ///
+    /// If set, only the tokens equal to one of the `aliases` request the help, which don't
+    /// need to be added to the [`Options`], and abbreviations never do.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{DefaultParser, Options, ParseErr, Parser};
+    ///
+    /// let mut parser = DefaultParser::builder().set_auto_help(true).set_help_aliases(&["-h", "-?"]).build();
+    /// assert!(matches!(parser.parse_args(&Options::new(), &["-?"]), Err(ParseErr::HelpRequested)));
+    /// ```
+    pub fn set_help_aliases(mut self, aliases: &[&str]) -> Self {
+        self.help_aliases = aliases.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    /// Set the version to handle the option with long name `version` automatically.
+    ///
+    /// Like [`Self::set_auto_help`], the version is printed to stdout and the program exits
+    /// with code 0, or [`ParseErr::VersionRequested`] is returned.
+    pub fn set_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_owned());
+        self
+    }
+
+    /// Set the formatter to print the help automatically, see [`Self::set_auto_help`].
+    pub fn set_help_formatter(mut self, formatter: HelpFormatter) -> Self {
+        self.help_formatter = Some(formatter);
+        self
+    }
+
+    /// Set whether to check the values of options with [`ValueHint::FilePath`] or
+    /// [`ValueHint::DirPath`] are existing paths, which is `false` by default.
+    ///
+    /// A file path must exist and a directory path must be a directory, otherwise
+    /// [`ParseErr::InvalidPath`] is returned. The values from defaults are also checked.
+    pub fn set_validate_paths(mut self, validate_paths: bool) -> Self {
+        self.validate_paths = validate_paths;
+        self
+    }
+
+    /// Set the check of paths for [`Self::set_validate_paths`], which uses
+    /// [`Path::exists`] and [`Path::is_dir`] by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anpcli::{AnpOption, DefaultParser, Options, ParseErr, Parser, ValueHint};
+    ///
+    /// let mut options = Options::new();
+    /// options.add_option(AnpOption::builder().option("f").has_arg(true).value_hint(ValueHint::FilePath).build().unwrap());
+    ///
+    /// let mut parser = DefaultParser::builder()
+    ///     .set_validate_paths(true)
+    ///     .set_path_checker(|path, _| path.starts_with("/data"))
+    ///     .build();
+    /// assert!(parser.parse_args(&options, &["-f", "/data/a.txt"]).is_ok());
+    /// assert!(matches!(parser.parse_args(&options, &["-f", "/tmp/a.txt"]), Err(ParseErr::InvalidPath { .. })));
+    /// ```
+    pub fn set_path_checker<F>(mut self, checker: F) -> Self
+        where F: Fn(&Path, ValueHint) -> bool + 'static {
+        self.path_checker = Some(Box::new(checker));
+        self
+    }
+
+    /// Set the exit code for usage errors, which is 1 by default. Some tools use 2
+    /// following the GNU conventions.
+    ///
+    /// The code is used by [`Parser::parse_or_exit`], and the exiting accessors of the
+    /// parsed [`CommandLine`] like [`CommandLine::get_expected_value`].
+    pub fn set_usage_exit_code(mut self, code: i32) -> Self {
+        self.usage_exit_code = code;
+        self
+    }
 }
 
 impl DefaultParser {
@@ -108,22 +447,239 @@ impl DefaultParser {
         ParserBuilder {
             allow_partial_matching: true,
             strip_leading_and_trailing_quotes: None,
+            quote_chars: vec!['"'],
             stop_at_non_option: false,
+            skip_program_name: false,
+            expand_at_files: false,
+            os_lossy: false,
+            collect_unknown: false,
+            allow_negative_numbers: false,
+            allow_duplicate_options: true,
+            env_prefix: None,
+            auto_help: false,
+            help_aliases: vec![],
+            version: None,
+            help_formatter: None,
+            usage_exit_code: 1,
+            validate_paths: false,
+            path_checker: None,
         }
     }
+}
+
+impl Parser for DefaultParser {
+    fn parse(&mut self, options: &Options) -> Result<CommandLine, ParseErr> {
+        let result = self.parse_tokens(options, env::args().collect(), true);
+        self.exit_on_help_or_version(options, result, None)
+    }
+
+    fn parse_or_exit(&mut self, options: &Options, formatter: &HelpFormatter) -> CommandLine {
+        let result = self.parse_tokens(options, env::args().collect(), true);
+        let result = self.exit_on_help_or_version(options, result, Some(formatter));
+        if let Ok(cmd) = result {
+            return cmd;
+        } else {
+            eprintln!("{}", formatter.wrap_text(&result.err().unwrap().to_string(), 0));
+            println!("{}", "-".repeat(formatter.get_width()));
+            // exiting anyway, nothing to do if the help can't be printed
+            let _ = formatter.print_help(&mut stdout(), &options);
+            exit(self.usage_exit_code);
+        }
+    }
+
+    fn parse_args<T>(&mut self, options: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr>
+        where T: ToString {
+        let arguments = arguments.iter().map(|a| a.to_string()).collect();
+        self.parse_tokens(options, arguments, self.skip_program_name)
+    }
+
+    fn parse_iter<I, S>(&mut self, options: &Options, arguments: I) -> Result<CommandLine, ParseErr>
+        where I: IntoIterator<Item=S>, S: ToString {
+        let arguments = arguments.into_iter().map(|a| a.to_string()).collect();
+        self.parse_tokens(options, arguments, self.skip_program_name)
+    }
+
+    fn parse_os(&mut self, options: &Options) -> Result<CommandLine, ParseErr> {
+        let arguments: Vec<_> = env::args_os().collect();
+        let arguments = self.convert_os_args(&arguments)?;
+        let result = self.parse_tokens(options, arguments, true);
+        self.exit_on_help_or_version(options, result, None)
+    }
+
+    fn parse_args_os<T>(&mut self, options: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr>
+        where T: AsRef<OsStr> {
+        let arguments = self.convert_os_args(arguments)?;
+        self.parse_tokens(options, arguments, self.skip_program_name)
+    }
+}
+
+impl DefaultParser {
+    /// Print the help or version and exit with code 0 if requested, see
+    /// [`ParserBuilder::set_auto_help`].
+    fn exit_on_help_or_version(&self, options: &Options, result: Result<CommandLine, ParseErr>,
+                               formatter: Option<&HelpFormatter>) -> Result<CommandLine, ParseErr> {
+        match result {
+            Err(ParseErr::HelpRequested) => {
+                let program = env::args().next()
+                    .and_then(|p| PathBuf::from(p).file_name().map(|f| f.to_string_lossy().into_owned()))
+                    .unwrap_or_default();
+                let default = HelpFormatter::new(&program);
+                let formatter = formatter.or(self.help_formatter.as_ref()).unwrap_or(&default);
+                // exiting anyway, nothing to do if the help can't be printed
+                let _ = formatter.print_help(&mut stdout(), options);
+                exit(0);
+            }
+            Err(ParseErr::VersionRequested) => {
+                println!("{}", self.version.as_deref().unwrap_or_default());
+                exit(0);
+            }
+            result => result,
+        }
+    }
+
+    fn convert_os_args<T: AsRef<OsStr>>(&self, arguments: &[T]) -> Result<Vec<String>, ParseErr> {
+        if self.os_lossy {
+            Ok(arguments.iter().map(|argument| argument.as_ref().to_string_lossy().into_owned()).collect())
+        } else {
+            to_utf8_args(arguments)
+        }
+    }
+
+    fn parse_tokens(&self, options: &Options, arguments: Vec<String>, has_program_name: bool)
+                    -> Result<CommandLine, ParseErr> {
+        // `Options::add_option` can't reject a second trailing var arg option
+        let trailing: Vec<String> = options.get_options_in_order().iter()
+            .filter(|option| option.is_trailing_var_arg())
+            .map(|option| option.get_key().to_owned())
+            .collect();
+        if trailing.len() > 1 {
+            return Err(ParseErr::ProcessingErr {
+                desc: format!("options '{}' and '{}' are both trailing var arg options", trailing[0], trailing[1]),
+                source: None,
+            });
+        }
+
+        let mut state = ParseState {
+            parser: self,
+            options,
+            cmd: CommandLine::builder().exit_code(self.usage_exit_code).build(),
+            current_token: None,
+            current_token_index: 0,
+            current_index: 0,
+            current_option: None,
+            skip_parsing: false,
+            expected_opts: Vec::from(options.get_required_options()),
+            selected_groups: vec![],
+        };
+        state.cmd.set_requirements(state.expected_opts.iter().map(|r| r.borrow().clone()).collect());
+
+        let mut arguments = arguments.into_iter();
+        if has_program_name {
+            if let Some(program_name) = arguments.next() {
+                state.cmd.set_program_name(&program_name);
+                state.current_index = 1;
+            }
+        }
+
+        let arguments = if self.expand_at_files {
+            state.expand_at_files(arguments.collect(), &mut vec![])?
+        } else {
+            arguments.collect()
+        };
+
+        for argument in arguments {
+            state.handle_token(argument)?;
+            state.current_index += 1;
+        }
+
+        let eager = state.cmd.get_options().iter().any(|o| o.is_eager());
+        if !eager {
+            state.check_required_args()?;
+        }
+
+        state.check_args_range()?;
+
+        state.handle_defaults()?;
+
+        if self.validate_paths {
+            state.check_paths()?;
+        }
+
+        if !eager {
+            state.check_required_options()?;
+        }
+
+        Ok(state.cmd)
+    }
+}
+
+/// The state of a single parsing, borrowing the [`Options`] being parsed.
+struct ParseState<'a> {
+    parser: &'a DefaultParser,
+    options: &'a Options,
+    cmd: CommandLine,
+    current_token: Option<String>,
+    // the index of the current token, which is `current_index` when handling the token
+    current_token_index: usize,
+    // the index of the current token in the arguments, including the program name
+    current_index: usize,
+    current_option: Option<Rc<RefCell<AnpOption>>>,
+    skip_parsing: bool,
+    expected_opts: Vec<Rc<RefCell<Required>>>,
+    // the selected option key of each option group, the groups of `options` are never mutated
+    selected_groups: Vec<(Rc<HashRefCellGroup>, String)>,
+}
+
+impl ParseState<'_> {
+    /// The position of the token being handled, or the last token after handling all.
+    fn token_position(&self) -> Option<TokenPosition> {
+        self.current_token.as_ref().map(|token| TokenPosition { index: self.current_token_index, token: token.to_owned() })
+    }
 
     fn check_required_args(&self) -> Result<(), ParseErr> {
         if let Some(opt) = &self.current_option {
             if opt.borrow().requires_arg() {
-                return Err(ParseErr::MissingArgument(opt.borrow().clone()));
+                return Err(ParseErr::MissingArgument(opt.borrow().clone(), self.token_position()));
             }
         }
         return Ok(());
     }
 
+    fn check_args_range(&self) -> Result<(), ParseErr> {
+        for option in self.cmd.get_options() {
+            if option.get_args().is_range() && !option.has_optional_arg()
+                && option.get_values::<String>().len() < option.get_args().get_min() {
+                return Err(ParseErr::MissingArgument(option.clone(), None));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_paths(&self) -> Result<(), ParseErr> {
+        for option in self.cmd.get_options() {
+            let hint = match option.get_value_hint() {
+                Some(hint @ (ValueHint::FilePath | ValueHint::DirPath)) => hint,
+                _ => continue,
+            };
+            for value in option.get_values::<String>() {
+                let value = value.unwrap();
+                let path = Path::new(&value);
+                let exists = match &self.parser.path_checker {
+                    Some(checker) => checker(path, hint),
+                    None if hint == ValueHint::DirPath => path.is_dir(),
+                    None => path.exists(),
+                };
+                if !exists {
+                    return Err(ParseErr::InvalidPath { option: option.get_key().to_owned(), path: value });
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn check_required_options(&self) -> Result<(), ParseErr> {
-        if !self.expected_opts.as_ref().unwrap().is_empty() {
-            let opts = self.expected_opts.as_ref().unwrap().iter()
+        if !self.expected_opts.is_empty() {
+            let opts = self.expected_opts.iter()
                 .map(|r| r.borrow().clone())
                 .collect::<Vec<Required>>();
             return Err(ParseErr::MissingOption(opts));
@@ -132,33 +688,69 @@ impl DefaultParser {
     }
 
     fn get_matching_long_options(&self, token: &str) -> Vec<String> {
-        if self.allow_partial_matching {
-            return self.options.as_ref().unwrap().get_matching_options(token);
-        }
-        if self.options.as_ref().unwrap().has_long_option(token) {
-            return vec![self.options.as_ref().unwrap().get_option(token).unwrap().borrow()
-                .get_long_opt().unwrap().to_owned()];
+        let mut matching_opts = if self.parser.allow_partial_matching {
+            self.options.get_matching_options(token)
+        } else if self.options.has_long_option(token) {
+            vec![Util::strip_leading_hyphens(token).to_owned()]
+        } else {
+            vec![]
+        };
+        // single hyphen long options are not recognized with double hyphens
+        if token.starts_with("--") {
+            matching_opts.retain(|long_opt| {
+                !self.options.get_long_option(long_opt).is_some_and(|o| o.borrow().is_single_hyphen_long())
+            });
         }
-        return vec![];
+        matching_opts
     }
 
-    fn handle_concatenated_options(&mut self, token: &str) -> Result<(), ParseErr> {
-        for (i, ch) in token.chars().enumerate() {
-            if i == 0 {
+    fn expand_at_files(&self, arguments: Vec<String>, visiting: &mut Vec<PathBuf>) -> Result<Vec<String>, ParseErr> {
+        let mut expanded = vec![];
+        for argument in arguments {
+            if !argument.starts_with('@') || argument.len() == 1 {
+                expanded.push(argument);
                 continue;
             }
 
-            if let Some(option) = self.options.as_ref().unwrap().get_option(&ch.to_string()) {
-                self.handle_option(&option)?;
-            } else {
-                self.handle_unknown_token(if self.stop_at_non_option && i > 1 { &token[i..] } else { token })?;
-                break;
+            let path = &argument[1..];
+            let arg_file_err = |source: io::Error| ParseErr::ArgFileError { path: path.to_string(), source };
+
+            let canonical_path = fs::canonicalize(path).map_err(arg_file_err)?;
+            if visiting.contains(&canonical_path) {
+                return Err(arg_file_err(io::Error::new(
+                    io::ErrorKind::InvalidInput, "the argument file references itself recursively")));
             }
+            let content = fs::read_to_string(&canonical_path).map_err(arg_file_err)?;
 
+            visiting.push(canonical_path);
+            expanded.append(&mut self.expand_at_files(Util::split_arg_file(&content), visiting)?);
+            visiting.pop();
+        }
+        Ok(expanded)
+    }
+
+    fn handle_concatenated_options(&mut self, token: &str) -> Result<(), ParseErr> {
+        // `i` is the byte offset of `ch`, the leading `-` is skipped
+        for (i, ch) in token.char_indices().skip(1) {
+            let option = match self.options.get_option(&ch.to_string()) {
+                Some(option) => option,
+                None => {
+                    // when stopping at non option, the remaining chars of `-xyz` after the
+                    // processed options are the first argument, e.g. `yz` if `-x` is known
+                    let processed = i > 1;
+                    self.handle_unknown_token(if self.parser.stop_at_non_option && processed { &token[i..] } else { token })?;
+                    break;
+                }
+            };
+            self.handle_option(&option)?;
+
+            // the remaining chars are the value of the first option taking an argument,
+            // e.g. `archive` of `-czfarchive` if `-f` takes an argument
+            let rest = &token[i + ch.len_utf8()..];
             if let Some(cur_option) = self.current_option.as_ref() {
-                if token.chars().count() != (i + 1) {
+                if !rest.is_empty() {
                     let result = cur_option.borrow_mut().add_value_for_processing(
-                        self.strip_leading_and_trailing_quotes_default_off(&token[i + 1..]));
+                        self.strip_leading_and_trailing_quotes_default_off(rest));
                     if result.is_err() {
                         return Err(ParseErr::ProcessingErr {
                             source: Some(result.unwrap_err()),
@@ -173,6 +765,10 @@ impl DefaultParser {
     }
 
     fn handle_long_option(&mut self, token: &str) -> Result<(), ParseErr> {
+        if let Some(option) = self.get_negated_option(token) {
+            return self.handle_negated_option(&option);
+        }
+
         if token.find('=').is_none() {
             self.handle_long_option_without_equal(token)
         } else {
@@ -189,15 +785,15 @@ impl DefaultParser {
         let matching_opts = self.get_matching_long_options(opt);
         if matching_opts.is_empty() {
             self.handle_unknown_token(&self.current_token.as_ref().unwrap().to_owned())
-        } else if matching_opts.len() > 1 && !self.options.as_ref().unwrap().has_long_option(opt) {
-            Err(ParseErr::AmbiguousOption { input_opt: opt.to_string(), matching_opts })
+        } else if matching_opts.len() > 1 && !self.options.has_long_option(opt) {
+            Err(ParseErr::AmbiguousOption { input_opt: opt.to_string(), matching_opts, position: self.token_position() })
         } else {
-            let key = if self.options.as_ref().unwrap().has_long_option(opt) {
+            let key = if self.options.has_long_option(opt) {
                 opt
             } else {
                 matching_opts.get(0).unwrap()
             };
-            let option = self.options.as_ref().unwrap().get_option(key).unwrap();
+            let option = self.options.get_option(key).unwrap();
 
             if option.borrow().accepts_arg() {
                 self.handle_option(&option)?;
@@ -223,26 +819,50 @@ impl DefaultParser {
 
         if matching_opts.is_empty() {
             self.handle_unknown_token(&self.current_token.as_ref().unwrap().to_owned())
-        } else if matching_opts.len() > 1 && !self.options.as_ref().unwrap().has_long_option(token) {
-            Err(ParseErr::AmbiguousOption { matching_opts, input_opt: token.to_string() })
+        } else if matching_opts.len() > 1 && !self.options.has_long_option(token) {
+            Err(ParseErr::AmbiguousOption { matching_opts, input_opt: token.to_string(), position: self.token_position() })
         } else {
-            let key = if self.options.as_ref().unwrap().has_long_option(token) {
+            let key = if self.options.has_long_option(token) {
                 token
             } else {
                 matching_opts.get(0).unwrap()
             };
-            self.handle_option(&self.options.as_ref().unwrap().get_option(key).unwrap())
+            self.handle_option(&self.options.get_option(key).unwrap())
         }
     }
 
+    fn handle_negated_option(&mut self, option: &Rc<RefCell<AnpOption>>) -> Result<(), ParseErr> {
+        let negated = Rc::new(RefCell::new(option.borrow().clone()));
+        negated.borrow_mut().set_negated(true);
+        self.handle_option(&negated)
+    }
+
     fn handle_option(&mut self, option: &Rc<RefCell<AnpOption>>) -> Result<(), ParseErr> {
-        self.check_required_args()?;
+        let long_opt = option.borrow().get_long_opt().cloned();
+        if self.parser.auto_help && self.parser.help_aliases.is_empty() && long_opt.as_deref() == Some("help") {
+            return Err(ParseErr::HelpRequested);
+        }
+        if self.parser.version.is_some() && long_opt.as_deref() == Some("version") {
+            return Err(ParseErr::VersionRequested);
+        }
+
+        if !option.borrow().is_eager() {
+            self.check_required_args()?;
+        }
+
+        let key = option.borrow().get_key().to_owned();
+        let repeatable = option.borrow().is_property()
+            || matches!(option.borrow().get_args(), ArgCount::Unlimited | ArgCount::Range { max: None, .. });
+        if !self.parser.allow_duplicate_options && option.borrow().has_arg() && !repeatable
+            && self.cmd.has_option(&key) {
+            return Err(ParseErr::DuplicateOption(key));
+        }
 
         let option = Rc::new(RefCell::new(option.borrow().clone()));
 
         self.update_required_options(option.borrow().deref())?;
 
-        self.cmd.as_mut().unwrap().add_option(Rc::clone(&option));
+        self.cmd.add_option(Rc::clone(&option));
 
         if option.borrow().has_arg() {
             self.current_option = Some(option);
@@ -254,38 +874,65 @@ impl DefaultParser {
     }
 
     fn handle_defaults(&mut self) -> Result<(), ParseErr> {
-        if !self.options.as_ref().unwrap().has_defaults() {
-            return Ok(());
+        let options = self.options;
+        if let Some(defaults) = options.get_defaults() {
+            for (option, value) in defaults {
+                if options.get_option(option).is_none() {
+                    return Err(ParseErr::UndefinedDefaultOption { option: option.to_string(), value: value.to_string() });
+                }
+            }
         }
-        let defaults = self.options.as_ref().unwrap().get_defaults().unwrap().clone();
-        for (option, value) in &defaults {
-            if self.options.as_ref().unwrap().get_option(option).is_some() {
-                let opt = self.options.as_ref().unwrap().get_option(option).unwrap();
-                let group = self.options.as_ref().unwrap().get_option_group(opt.borrow().deref());
-                let selected = group.is_some() && group.unwrap().borrow().get_selected().is_some();
-
-                let mut opt_mut = opt.borrow_mut();
-                if !self.cmd.as_ref().unwrap().has_option(option) && !selected {
-                    if opt_mut.has_arg() {
-                        if opt_mut.get_values::<String>().is_empty() {
-                            let result = opt_mut.add_value_for_processing(value);
-                            if result.is_err() {
-                                return Err(ParseErr::ProcessingErr {
-                                    source: Some(result.unwrap_err()),
-                                    desc: format!("Error occurred when handling default value: {}", option),
-                                });
-                            }
-                        }
-                    } else if "yes" != value.to_lowercase() && "true" != value.to_lowercase() && "1" != value {
-                        continue;
-                    }
 
-                    self.handle_option(&opt)?;
-                    self.current_option = None;
-                }
+        // the environment variables take precedence over the defaults of `Options`,
+        // which take precedence over the default value of the option
+        let mut defaults = vec![];
+        for option in options.get_options_in_order() {
+            let env_name = self.parser.env_prefix.as_ref().zip(option.get_long_opt())
+                .map(|(prefix, long_opt)| format!("{}_{}", prefix, long_opt.to_uppercase().replace('-', "_")));
+            if let Some((name, value)) = env_name.and_then(|name| env::var(&name).ok().map(|value| (name, value))) {
+                defaults.push((option.get_key().to_owned(), value, ValueSource::Env,
+                               format!("environment variable '{}'", name)));
+            } else if let Some(value) = options.get_option_default(&option) {
+                defaults.push((option.get_key().to_owned(), value.to_owned(), ValueSource::Default,
+                               "default value".to_string()));
+            }
+        }
+
+        for (key, value, value_source, source) in defaults {
+            let opt = self.options.get_option(&key).unwrap();
+            let has_arg = opt.borrow().has_arg();
+            // the default of an option without argument is a flag
+            let flag = if has_arg {
+                true
             } else {
-                return Err(ParseErr::UndefinedDefaultOption { option: option.to_string(), value: value.to_string() });
+                match Util::parse_bool(&value) {
+                    Some(flag) => flag,
+                    None => return Err(ParseErr::ProcessingErr {
+                        source: None,
+                        desc: format!("invalid {} '{}' for option '{}', expected one of yes, true, on, 1, no, false, off, 0",
+                                      source, value, key),
+                    }),
+                }
+            };
+
+            let group = self.options.get_option_group(opt.borrow().deref());
+            let selected = group.is_some_and(|g| self.get_selected(&g).is_some());
+            if self.cmd.has_option(&key) || selected || !flag {
+                continue;
             }
+
+            self.handle_option(&opt)?;
+            self.cmd.set_value_source(&key, value_source);
+            if has_arg {
+                let result = self.current_option.as_ref().unwrap().borrow_mut().add_value_for_processing(&value);
+                if result.is_err() {
+                    return Err(ParseErr::ProcessingErr {
+                        source: Some(result.unwrap_err()),
+                        desc: format!("Error occurred when handling {} of option: {}", source, key),
+                    });
+                }
+            }
+            self.current_option = None;
         }
         Ok(())
     }
@@ -293,19 +940,34 @@ impl DefaultParser {
     fn handle_short_and_long_option(&mut self, token: &str) -> Result<(), ParseErr> {
         let t = Util::strip_leading_hyphens(token);
 
+        if let Some(option) = self.get_property_option(t) {
+            // -Pkey=value
+            let value = &t[option.borrow().get_opt().unwrap().len()..];
+            self.handle_option(&option)?;
+            let result = self.current_option.as_ref().unwrap().borrow_mut().add_value_for_processing(value);
+            if let Err(err) = result {
+                return Err(ParseErr::ProcessingErr {
+                    source: Some(err),
+                    desc: format!("Error occurred when parsing property: {}", token),
+                });
+            }
+            self.current_option = None;
+            return Ok(());
+        }
+
         let pos = t.find('=');
 
-        if t.len() == 1 {
+        if t.chars().count() == 1 {
             // -s
-            if self.options.as_ref().unwrap().has_short_option(t) {
-                self.handle_option(self.options.as_ref().unwrap().get_option(t).as_ref().unwrap())?;
+            if self.options.has_short_option(t) {
+                self.handle_option(self.options.get_option(t).as_ref().unwrap())?;
             } else {
                 self.handle_unknown_token(token)?;
             }
         } else if pos.is_none() {
             // no equal sign found (-xxx)
-            if self.options.as_ref().unwrap().has_short_option(t) {
-                self.handle_option(self.options.as_ref().unwrap().get_option(t).as_ref().unwrap())?;
+            if self.options.has_short_option(t) {
+                self.handle_option(self.options.get_option(t).as_ref().unwrap())?;
             } else if !self.get_matching_long_options(t).is_empty() {
                 // -l or -L
                 self.handle_long_option_without_equal(token)?;
@@ -318,9 +980,10 @@ impl DefaultParser {
             let opt = &t[..pos.unwrap()];
             let value = &t[pos.unwrap() + 1..];
 
-            if opt.len() == 1 {
+            let first_len = opt.chars().next().map_or(0, |c| c.len_utf8());
+            if opt.len() == first_len {
                 // -S=V
-                let option = self.options.as_ref().unwrap().get_option(opt);
+                let option = self.options.get_option(opt);
                 if option.as_ref().is_some_and(|o| o.borrow().accepts_arg()) {
                     self.handle_option(option.as_ref().unwrap())?;
                     let result = self.current_option.as_ref().unwrap().borrow_mut().add_value_for_processing(value);
@@ -334,6 +997,10 @@ impl DefaultParser {
                 } else {
                     self.handle_unknown_token(token)?;
                 }
+            } else if self.get_matching_long_options(opt).is_empty()
+                && self.options.get_option(&opt[..first_len]).is_some_and(|o| o.borrow().accepts_arg()) {
+                // -SV=V, the value is attached to the short option like -Dkey=value
+                self.handle_concatenated_options(token)?;
             } else {
                 // -L=V or -L=V
                 self.handle_long_option_with_equal(token)?;
@@ -344,11 +1011,28 @@ impl DefaultParser {
 
     fn handle_token(&mut self, token: String) -> Result<(), ParseErr> {
         self.current_token = Some(token.to_owned());
+        self.current_token_index = self.current_index;
 
         if self.skip_parsing {
-            self.cmd.as_mut().unwrap().add_arg(&token);
+            self.cmd.add_arg_at(&token, self.current_index);
+        } else if self.current_option.as_ref().is_some_and(|o| o.borrow().is_trailing_var_arg()
+            && o.borrow().get_stop_token() != Some(token.as_str())) {
+            // the trailing var arg option takes the token verbatim
+            let result = self.current_option.as_ref().unwrap().borrow_mut().add_value_for_processing(&token);
+            if let Err(e) = result {
+                return Err(ParseErr::ProcessingErr {
+                    desc: format!("Error occurred when handling token: {}", token),
+                    source: Some(e),
+                });
+            }
         } else if "--" == token {
             self.skip_parsing = true;
+        } else if self.parser.auto_help && self.parser.help_aliases.contains(&token) {
+            return Err(ParseErr::HelpRequested);
+        } else if self.current_option.as_ref().is_some_and(|o| o.borrow().get_stop_token() == Some(token.as_str())) {
+            // the stop token ends the values of the current option
+            self.check_required_args()?;
+            self.current_option = None;
         } else if self.current_option.as_ref().is_some_and(|o| o.borrow().accepts_arg() && self.is_argument(&token)) {
             let result = self.current_option.as_ref().unwrap().borrow_mut().add_value_for_processing(
                 self.strip_leading_and_trailing_quotes_default_on(&token));
@@ -358,6 +1042,11 @@ impl DefaultParser {
                     source: Some(result.unwrap_err()),
                 });
             }
+        } else if self.parser.allow_negative_numbers && self.is_negative_number(&token) {
+            self.cmd.add_arg_at(&token, self.current_index);
+            if self.parser.stop_at_non_option {
+                self.stop_parsing();
+            }
         } else if token.starts_with("--") {
             self.handle_long_option(&token)?;
         } else if token.starts_with("-") && token != "-" {
@@ -370,26 +1059,96 @@ impl DefaultParser {
     }
 
     fn handle_unknown_token(&mut self, token: &str) -> Result<(), ParseErr> {
-        if token.starts_with("-") && token.len() > 1 && !self.stop_at_non_option {
-            return Err(ParseErr::UnrecognizedOption(token.to_string()));
+        if token.starts_with("-") && token.len() > 1 && self.parser.collect_unknown {
+            self.cmd.add_unknown_option(token);
+            return Ok(());
         }
 
-        self.cmd.as_mut().unwrap().add_arg(token);
-        if self.stop_at_non_option {
-            self.skip_parsing = true;
+        if token.starts_with("-") && token.len() > 1 && !self.parser.stop_at_non_option {
+            if let Some(expansion) = self.get_abbreviation_expansion(token) {
+                return Err(ParseErr::AbbreviatedOption {
+                    option: token.split('=').next().unwrap().to_string(),
+                    expansion: format!("--{}", expansion),
+                });
+            }
+            return Err(ParseErr::UnrecognizedOption {
+                option: token.to_string(),
+                suggestion: self.suggest_long_option(token),
+                position: self.token_position(),
+            });
+        }
+
+        self.cmd.add_arg_at(token, self.current_index);
+        if self.parser.stop_at_non_option {
+            self.stop_parsing();
         }
         Ok(())
     }
 
+    /// Stop parsing options at the current non-option argument.
+    fn stop_parsing(&mut self) {
+        self.skip_parsing = true;
+        self.cmd.set_stopped_at(self.current_index);
+    }
+
+    /// The only long option that `token` abbreviates, if partial matching is disabled.
+    fn get_abbreviation_expansion(&self, token: &str) -> Option<String> {
+        if self.parser.allow_partial_matching || !token.starts_with("--") {
+            return None;
+        }
+        let mut expansions = self.options.get_matching_options(token.split('=').next().unwrap());
+        expansions.retain(|long_opt| {
+            !self.options.get_long_option(long_opt).is_some_and(|o| o.borrow().is_single_hyphen_long())
+        });
+        if expansions.len() == 1 {
+            expansions.pop()
+        } else {
+            None
+        }
+    }
+
+    fn suggest_long_option(&self, token: &str) -> Option<String> {
+        let name = Util::strip_leading_hyphens(token);
+        let name = name.split('=').next().unwrap();
+        if name.chars().count() < 2 {
+            return None;
+        }
+
+        let mut suggestion = None;
+        let mut min_distance = MAX_SUGGESTION_DISTANCE + 1;
+        for option in self.options.get_options_in_order() {
+            if let Some(long_opt) = option.get_long_opt() {
+                let distance = Util::edit_distance(name, long_opt);
+                if distance < min_distance {
+                    min_distance = distance;
+                    suggestion = Some(format!("--{}", long_opt));
+                }
+            }
+        }
+        suggestion
+    }
+
     fn is_argument(&self, token: &str) -> bool {
         !self.is_option(token) || self.is_negative_number(token)
     }
 
+    fn get_negated_option(&self, token: &str) -> Option<Rc<RefCell<AnpOption>>> {
+        // an existing long option takes precedence over the negated form
+        if self.options.has_long_option(token) {
+            return None;
+        }
+        self.options.get_negated_option(token)
+    }
+
     fn is_long_option(&self, token: &str) -> bool {
         if !token.starts_with("-") || token.len() == 1 {
             return false;
         }
 
+        if self.get_negated_option(token).is_some() {
+            return true;
+        }
+
         let pos = token.find('=');
         let t = if pos.is_none() { token } else { &token[..pos.unwrap()] };
 
@@ -401,40 +1160,73 @@ impl DefaultParser {
     }
 
     fn is_negative_number(&self, token: &str) -> bool {
-        token.parse::<f64>().is_ok()
+        if !token.starts_with('-') || token.starts_with("--") {
+            return false;
+        }
+
+        // the value can be a list like `-1,2` if the current option has a value separator
+        let separator = self.current_option.as_ref()
+            .and_then(|option| option.borrow().get_value_separator_str().map(|sep| sep.to_owned()));
+        match separator {
+            Some(sep) if !sep.is_empty() => token.split(sep.as_str()).all(Util::is_number),
+            _ => Util::is_number(token),
+        }
     }
 
     fn is_option(&self, token: &str) -> bool {
         self.is_long_option(token) || self.is_short_option(token)
     }
 
+    fn get_property_option(&self, token: &str) -> Option<Rc<RefCell<AnpOption>>> {
+        let options = self.options;
+        let name = token.split('=').next().unwrap();
+        if options.has_short_option(name) || options.has_long_option(name) {
+            return None;
+        }
+
+        for option in options.get_options_in_order() {
+            if let Some(opt) = option.get_opt() {
+                if option.is_property() && token.len() > opt.len() && token.starts_with(opt.as_str()) {
+                    return options.get_option(opt);
+                }
+            }
+        }
+        None
+    }
+
     fn is_short_option(&self, token: &str) -> bool {
         if !token.starts_with("-") || token.len() == 1 {
             return false;
         }
 
+        if self.get_property_option(&token[1..]).is_some() {
+            return true;
+        }
+
         let pos = token.find('=');
         let opt_name = if pos.is_none() { &token[1..] } else { &token[1..pos.unwrap()] };
-        if self.options.as_ref().unwrap().has_short_option(opt_name) {
+        if self.options.has_short_option(opt_name) {
             return true;
         }
-        if !opt_name.is_empty() && self.options.as_ref().unwrap().has_short_option(&opt_name[..1]) {
-            return true;
+        if let Some(c) = opt_name.chars().next() {
+            if self.options.has_short_option(&opt_name[..c.len_utf8()]) {
+                return true;
+            }
         }
         return false;
     }
 
     fn strip_leading_and_trailing_quotes_default_off<'a>(&self, token: &'a str) -> &'a str {
-        if self.strip_leading_and_trailing_quotes.unwrap_or(false) {
-            Util::strip_leading_and_trailing_quotes(token)
+        if self.parser.strip_leading_and_trailing_quotes.unwrap_or(false) {
+            Util::strip_quotes_with(token, &self.parser.quote_chars)
         } else {
             token
         }
     }
 
     fn strip_leading_and_trailing_quotes_default_on<'a>(&self, token: &'a str) -> &'a str {
-        if self.strip_leading_and_trailing_quotes.unwrap_or(true) {
-            Util::strip_leading_and_trailing_quotes(token)
+        if self.parser.strip_leading_and_trailing_quotes.unwrap_or(true) {
+            Util::strip_quotes_with(token, &self.parser.quote_chars)
         } else {
             token
         }
@@ -442,77 +1234,585 @@ impl DefaultParser {
 
     fn update_required_options(&mut self, option: &AnpOption) -> Result<(), ParseErr> {
         if option.is_required() {
-            let pos = self.expected_opts.as_ref().unwrap().iter()
+            let pos = self.expected_opts.iter()
                 .position(|r| r.borrow().deref() == &Required::OptKey(option.get_key().to_owned()));
             if pos.is_some() {
-                self.expected_opts.as_mut().unwrap().remove(pos.unwrap());
+                self.expected_opts.remove(pos.unwrap());
             }
         }
 
-        if let Some(group) = self.options.as_ref().unwrap().get_option_group(option) {
+        if let Some(group) = self.options.get_option_group(option) {
             if group.borrow().is_required() {
-                let pos = self.expected_opts.as_ref().unwrap().iter()
+                let pos = self.expected_opts.iter()
                     .position(|r| r.borrow().deref() == &Required::OptGroup(Rc::clone(&group)));
                 if pos.is_some() {
-                    self.expected_opts.as_mut().unwrap().remove(pos.unwrap());
+                    self.expected_opts.remove(pos.unwrap());
                 }
             }
 
-            let result = group.borrow_mut().set_selected(Some(option));
-            if result.is_err() {
-                return Err(ParseErr::ProcessingErr {
-                    source: Some(result.unwrap_err()),
-                    desc: format!("error occurred when updating required options"),
-                });
-            }
+            self.set_selected(group, option)?;
         }
 
         Ok(())
     }
+
+    fn get_selected(&self, group: &Rc<HashRefCellGroup>) -> Option<&String> {
+        self.selected_groups.iter()
+            .find(|(g, _)| Rc::ptr_eq(g, group))
+            .map(|(_, key)| key)
+    }
+
+    fn set_selected(&mut self, group: Rc<HashRefCellGroup>, option: &AnpOption) -> Result<(), ParseErr> {
+        match self.get_selected(&group) {
+            Some(selected) if selected != option.get_key() => Err(ParseErr::ProcessingErr {
+                source: Some(OptionErr::of(Some(option), "option group already selected")),
+                desc: "error occurred when updating required options".to_string(),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.cmd.add_group_selection(&group.borrow(), option.get_key());
+                self.selected_groups.push((group, option.get_key().to_owned()));
+                Ok(())
+            }
+        }
+    }
 }
 
-impl Parser for DefaultParser {
-    fn parse(&mut self, options: &Options) -> Result<CommandLine, ParseErr> {
-        self.parse_args(options, &env::args().collect::<Vec<String>>())
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::path::Path;
+
+    use crate::cmd::{CommandLine, ValueSource};
+    use crate::error::{ParseErr, TokenPosition};
+    use crate::format::HelpFormatter;
+    use crate::option::{AnpOption, OptionGroup, Options, ValueHint};
+    use crate::parser::{DefaultParser, Parser};
+
+    fn parse(stop_at_non_option: bool, arguments: &[&str]) -> Result<CommandLine, ParseErr> {
+        let mut options = Options::new();
+        options.add_option1("x", "a flag").unwrap();
+        options.add_option0("f", true, "an option with argument").unwrap();
+
+        let mut parser = DefaultParser::builder().set_stop_at_non_option(stop_at_non_option).build();
+        parser.parse_args(&options, arguments)
     }
 
-    fn parse_or_exit(&mut self, options: &Options, formatter: &HelpFormatter) -> CommandLine {
-        let result = self.parse(options);
-        if let Ok(cmd) = result {
-            return cmd;
-        } else {
-            let mut error = String::new();
-            formatter.render_wrapped_text_block(&mut error, 0, &format!("{}", result.err().unwrap()));
-            eprintln!("{}", error);
-            println!("{}", "-".repeat(formatter.get_width()));
-            formatter.print_help(&mut stdout(), &options);
-            exit(1);
+    #[test]
+    fn test_double_dash() {
+        for stop_at_non_option in [false, true] {
+            let cmd = parse(stop_at_non_option, &["-x", "--", "-x", "-y", "--", "arg"]).unwrap();
+            assert_eq!(1, cmd.get_options().len());
+            assert_eq!(vec!["-x", "-y", "--", "arg"], cmd.get_arg_list());
+
+            let cmd = parse(stop_at_non_option, &["-f", "v", "--", "-f"]).unwrap();
+            assert_eq!(Some("v".to_string()), cmd.get_value::<String>("f").map(|v| v.unwrap()));
+            assert_eq!(vec!["-f"], cmd.get_arg_list());
         }
     }
 
-    fn parse_args<T>(&mut self, options: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr>
-        where T: ToString {
-        self.options = Some(options.clone());
-        for group in self.options.as_mut().unwrap().get_option_groups() {
-            group.borrow_mut().set_selected(None).expect("should succeed");
+    #[test]
+    fn test_stop_at_non_option() {
+        let cmd = parse(true, &["-x", "arg", "-x", "--", "-y"]).unwrap();
+        assert_eq!(1, cmd.get_options().len());
+        assert_eq!(vec!["arg", "-x", "--", "-y"], cmd.get_arg_list());
+
+        let cmd = parse(true, &["-x", "-y", "-x", "--"]).unwrap();
+        assert_eq!(1, cmd.get_options().len());
+        assert_eq!(vec!["-y", "-x", "--"], cmd.get_arg_list());
+
+        let cmd = parse(true, &["-xy", "-x"]).unwrap();
+        assert!(cmd.has_option("x"));
+        assert_eq!(vec!["y", "-x"], cmd.get_arg_list());
+
+        let cmd = parse(true, &["--unknown=1", "-f"]).unwrap();
+        assert!(cmd.get_options().is_empty());
+        assert_eq!(vec!["--unknown=1", "-f"], cmd.get_arg_list());
+
+        assert_eq!(Some(1), parse(true, &["-x", "file", "-x"]).unwrap().stopped_at());
+        assert_eq!(None, parse(true, &["-x", "--", "file"]).unwrap().stopped_at());
+        assert_eq!(None, parse(false, &["-x", "file", "-x"]).unwrap().stopped_at());
+    }
+
+    #[test]
+    fn test_value_separator_str() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("p").number_of_args(3).value_separator_str("::").build().unwrap());
+        options.add_option(AnpOption::builder().option("q").number_of_args(2).value_separator_str("::").build().unwrap());
+        options.add_option(AnpOption::builder().option("r").has_args().value_separator_str("→").build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        let cmd = parser.parse_args(&options, &["-p", "a::b::c", "-q", "a::b::c", "-r", "a→b→→c"]).unwrap();
+        let values = |opt: &str| cmd.get_values::<String>(opt).unwrap().into_iter().map(|v| v.unwrap()).collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b", "c"], values("p"));
+        assert_eq!(vec!["a", "b::c"], values("q"));
+        assert_eq!(vec!["a", "b", "", "c"], values("r"));
+    }
+
+    #[test]
+    fn test_stdin_arg() {
+        let cmd = parse(false, &["-f", "-", "-x"]).unwrap();
+        assert_eq!(Some("-".to_string()), cmd.get_value::<String>("f").map(|v| v.unwrap()));
+        assert!(cmd.has_option("x"));
+        assert!(!cmd.has_stdin_arg());
+
+        let cmd = parse(false, &["a", "-", "-x"]).unwrap();
+        assert_eq!(vec!["a", "-"], cmd.get_arg_list());
+        assert!(cmd.has_option("x"));
+        assert!(cmd.has_stdin_arg());
+    }
+
+    #[test]
+    fn test_not_stop_at_non_option() {
+        let cmd = parse(false, &["-x", "arg", "-x", "--", "-y"]).unwrap();
+        assert_eq!(2, cmd.get_options().len());
+        assert_eq!(vec!["arg", "-y"], cmd.get_arg_list());
+
+        let unrecognized = |option: &str, index: usize| ParseErr::UnrecognizedOption {
+            option: option.to_string(),
+            suggestion: None,
+            position: Some(TokenPosition { index, token: option.to_string() }),
+        };
+        assert_eq!(Some(unrecognized("-y", 1)), parse(false, &["arg", "-y"]).err());
+        assert_eq!(Some(unrecognized("-xy", 0)), parse(false, &["-xy"]).err());
+    }
+
+    #[test]
+    fn test_single_hyphen_long() {
+        let mut options = Options::new();
+        options.add_option0("p", false, "").unwrap();
+        options.add_option0("r", false, "").unwrap();
+        options.add_option0("o", false, "").unwrap();
+        options.add_option(AnpOption::builder().long_option("projecthelp").single_hyphen_long(true).build().unwrap());
+        options.add_option(AnpOption::builder().long_option("pro").single_hyphen_long(true).has_arg(true).build().unwrap());
+
+        for allow_partial_matching in [true, false] {
+            let mut parser = DefaultParser::builder().set_allow_partial_matching(allow_partial_matching).build();
+            let cmd = parser.parse_args(&options, &["-projecthelp", "-pro=x", "-pro", "y"]).unwrap();
+            assert!(cmd.has_option("projecthelp"));
+            assert!(!cmd.has_option("p") && !cmd.has_option("r") && !cmd.has_option("o"));
+            assert_eq!(Some(&vec!["x".to_string(), "y".to_string()]), cmd.to_value_map().get("pro"));
+
+            assert!(matches!(parser.parse_args(&options, &["--projecthelp"]),
+                Err(ParseErr::UnrecognizedOption { .. })));
         }
 
-        self.skip_parsing = false;
-        self.current_option = None;
-        self.expected_opts = Some(Vec::from(self.options.as_ref().unwrap().get_required_options()));
+        let cmd = DefaultParser::builder().set_allow_partial_matching(false).build().parse_args(&options, &["-pr"]).unwrap();
+        assert!(cmd.has_option("p") && cmd.has_option("r"));
 
-        self.cmd = Some(CommandLine::builder().build());
+        let help = HelpFormatter::new("ant").options_to_string(&options);
+        assert!(help.lines().any(|line| line.trim() == "-projecthelp"));
+        assert!(help.lines().any(|line| line.trim() == "-pro <arg>"));
+    }
 
-        for argument in arguments {
-            self.handle_token(argument.to_string())?;
+    #[test]
+    fn test_negative_numbers() {
+        let mut options = Options::new();
+        options.add_option0("n", true, "").unwrap();
+        options.add_option(AnpOption::builder().option("l").has_args().value_separator(',').build().unwrap());
+        options.add_option0("e", false, "").unwrap();
+
+        let mut parser = DefaultParser::builder().build();
+        for value in ["-5", "-5.0", "-5e2", "-.5", "-0x10"] {
+            let cmd = parser.parse_args(&options, &["-n", value]).unwrap();
+            assert_eq!(Some(value.to_string()), cmd.get_value::<String>("n").map(|v| v.unwrap()));
+        }
+        let cmd = parser.parse_args(&options, &["-l", "-1,2"]).unwrap();
+        assert_eq!(Some(&vec!["-1".to_string(), "2".to_string()]), cmd.to_value_map().get("l"));
+        assert!(parser.parse_args(&options, &["-5"]).is_err());
+
+        let mut parser = DefaultParser::builder().set_allow_negative_numbers(true).build();
+        let cmd = parser.parse_args(&options, &["-5", "-e", "-5e2", "-.5"]).unwrap();
+        assert_eq!(vec!["-5", "-5e2", "-.5"], cmd.get_arg_list());
+        assert!(cmd.has_option("e"));
+        assert!(parser.parse_args(&options, &["-inf"]).is_err());
+    }
+
+    #[test]
+    fn test_negated_option() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("c").long_option("color").negatable(true).build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        let cmd = parser.parse_args(&options, &["--no-color"]).unwrap();
+        assert!(cmd.has_option("color"));
+        assert!(cmd.has_option("c"));
+        assert_eq!(Some(false), cmd.get_flag("color"));
+
+        let cmd = parser.parse_args(&options, &["--no-color", "-c"]).unwrap();
+        assert_eq!(Some(true), cmd.get_flag("color"));
+    }
+
+    #[test]
+    fn test_short_option_value_forms() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("O").has_arg(true).build().unwrap());
+        options.add_option(AnpOption::builder().option("P").has_arg(true).optional_arg(true).build().unwrap());
+        options.add_option(AnpOption::builder().option("D").has_args().value_separator('=').build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        for opt in ["O", "P"] {
+            for args in [vec![format!("-{}2", opt)], vec![format!("-{}", opt), "2".to_string()], vec![format!("-{}=2", opt)]] {
+                let cmd = parser.parse_args(&options, &args).unwrap();
+                assert_eq!(Some(&vec!["2".to_string()]), cmd.to_value_map().get(opt), "{:?}", args);
+                assert!(cmd.get_arg_list().is_empty());
+            }
+        }
+
+        for args in [vec!["-Dk=v"], vec!["-D", "k=v"], vec!["-D=k=v"]] {
+            let cmd = parser.parse_args(&options, &args).unwrap();
+            assert_eq!(Some(&vec!["k".to_string(), "v".to_string()]), cmd.to_value_map().get("D"), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_stop_token() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("f").long_option("files").has_args().stop_token(";").build().unwrap());
+        options.add_option0("v", false, "").unwrap();
+
+        let mut parser = DefaultParser::builder().build();
+        let cmd = parser.parse_args(&options, &["--files", "a", "b", ";", "x", "-f", "c", ";", "y"]).unwrap();
+        assert_eq!(Some(&vec!["a".to_string(), "b".to_string(), "c".to_string()]), cmd.to_value_map().get("f"));
+        assert_eq!(vec!["x", "y"], cmd.get_arg_list());
+
+        // the stop token is only special right after the option values
+        let cmd = parser.parse_args(&options, &["-v", ";", "-f", "a"]).unwrap();
+        assert_eq!(vec![";"], cmd.get_arg_list());
+
+        assert!(matches!(parser.parse_args(&options, &["-f", ";"]), Err(ParseErr::MissingArgument(..))));
+    }
+
+    #[test]
+    fn test_abbreviation_disabled() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().long_option("verbose").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("version").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("level").has_arg(true).build().unwrap());
+
+        let mut parser = DefaultParser::builder().set_allow_partial_matching(false).build();
+        assert!(parser.parse_args(&options, &["--verbose", "--level=1"]).is_ok());
+
+        let err = parser.parse_args(&options, &["--verb"]).unwrap_err();
+        assert_eq!(ParseErr::AbbreviatedOption { option: "--verb".to_string(), expansion: "--verbose".to_string() }, err);
+        assert_eq!("parse error, unrecognized option '--verb'; abbreviations are disabled, did you mean '--verbose'?",
+                   err.to_string());
+        assert!(matches!(parser.parse_args(&options, &["--lev=1"]), Err(ParseErr::AbbreviatedOption { .. })));
+        assert!(matches!(parser.parse_args(&options, &["--ver"]), Err(ParseErr::UnrecognizedOption { .. })));
+    }
+
+    #[test]
+    fn test_env_prefix() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().long_option("max-depth").has_arg(true).default_value("1").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("dry-run").build().unwrap());
+
+        env::set_var("MYAPP_MAX_DEPTH", "3");
+        env::set_var("MYAPP_DRY_RUN", "yes");
+        let mut parser = DefaultParser::builder().set_env_prefix("MYAPP").build();
+        let cmd = parser.parse_args(&options, &[""; 0]).unwrap();
+        assert_eq!(Some(3), cmd.get_value::<i32>("max-depth").map(|v| v.unwrap()));
+        assert!(cmd.has_option("dry-run"));
+        assert_eq!(Some(ValueSource::Env), cmd.get_value_source("max-depth"));
+
+        let cmd = parser.parse_args(&options, &["--max-depth", "5"]).unwrap();
+        assert_eq!(Some(5), cmd.get_value::<i32>("max-depth").map(|v| v.unwrap()));
+        assert_eq!(Some(ValueSource::CommandLine), cmd.get_value_source("max-depth"));
+
+        let cmd = DefaultParser::builder().build().parse_args(&options, &[""; 0]).unwrap();
+        assert_eq!(Some(1), cmd.get_value::<i32>("max-depth").map(|v| v.unwrap()));
+        assert!(!cmd.has_option("dry-run"));
+
+        let mut required = Options::new();
+        required.add_option(AnpOption::builder().long_option("token").has_arg(true).required(true).build().unwrap());
+        env::set_var("MYAPP_TOKEN", "secret");
+        let cmd = parser.parse_args(&required, &[""; 0]).unwrap();
+        assert_eq!(vec![("token".to_string(), Some(ValueSource::Env))], cmd.requirement_report());
+        env::remove_var("MYAPP_TOKEN");
+
+        env::set_var("MYAPP_DRY_RUN", "maybe");
+        assert!(parser.parse_args(&options, &[""; 0]).is_err());
+        env::remove_var("MYAPP_MAX_DEPTH");
+        env::remove_var("MYAPP_DRY_RUN");
+    }
+
+    #[test]
+    fn test_explicit_options() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().long_option("level").has_arg(true).default_value("info").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("user").has_arg(true).build().unwrap());
+        options.add_option(AnpOption::builder().long_option("token").has_arg(true).build().unwrap());
+        options.add_option2("o", "output", true, "").unwrap();
+
+        env::set_var("EXPLICIT_USER", "alice");
+        env::set_var("EXPLICIT_TOKEN", "secret");
+        let mut parser = DefaultParser::builder().set_env_prefix("EXPLICIT").build();
+        let cmd = parser.parse_args(&options, &["--token", "override", "-o", "a.txt"]).unwrap();
+        env::remove_var("EXPLICIT_USER");
+        env::remove_var("EXPLICIT_TOKEN");
+
+        assert_eq!(vec!["token", "o"], cmd.explicit_options());
+        assert_eq!(Some(ValueSource::Env), cmd.get_value_source("user"));
+        assert_eq!(Some(ValueSource::Default), cmd.get_value_source("level"));
+        assert_eq!(Some("override".to_string()), cmd.get_value::<String>("token").map(|v| v.unwrap()));
+    }
+
+    #[test]
+    fn test_missing_option_group() {
+        let group = |required: bool, a: &str, b: &str| OptionGroup::new()
+            .required(required)
+            .add_option(AnpOption::builder().option(a).build().unwrap())
+            .add_option(AnpOption::builder().option(b).build().unwrap());
+        let mut options = Options::new();
+        options.add_required_option("f", "file", true, "").unwrap();
+        options.add_option_group(group(true, "a", "b"));
+        options.add_option_group(group(false, "x", "y"));
+
+        let mut parser = DefaultParser::builder().build();
+        let err = parser.parse_args(&options, &["-x"]).unwrap_err();
+        assert_eq!("parse error, missing options: 'f', one of (-a | -b)", err.to_string());
+        let err = parser.parse_args(&options, &["-f", "a.txt"]).unwrap_err();
+        assert_eq!("parse error, missing option one of (-a | -b)", err.to_string());
+        assert!(parser.parse_args(&options, &["-f", "a.txt", "-b"]).is_ok());
+    }
+
+    #[test]
+    fn test_args_with_index() {
+        let cmd = parse(false, &["a", "-x", "b", "-f", "c", "d", "--", "-x"]).unwrap();
+        assert_eq!(vec![(0, "a"), (2, "b"), (5, "d"), (7, "-x")], cmd.get_args_with_index());
+
+        let options = Options::new();
+        let cmd = DefaultParser::builder().set_skip_program_name(true).build()
+            .parse_args(&options, &["prog", "a", "b"]).unwrap();
+        assert_eq!(vec![(0, "prog"), (1, "a"), (2, "b")], cmd.get_args_with_index());
+    }
+
+    #[test]
+    fn test_args_range() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("r").args_range(2, Some(3)).build().unwrap());
+        let mut parser = DefaultParser::builder().build();
+
+        assert!(matches!(parser.parse_args(&options, &["-r", "a"]), Err(ParseErr::MissingArgument(..))));
+        let cmd = parser.parse_args(&options, &["-r", "a", "b", "c", "d"]).unwrap();
+        assert_eq!(Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]), cmd.get_raw_values("r"));
+        assert_eq!(vec!["d"], cmd.get_arg_list());
+    }
+
+    #[test]
+    fn test_usage_exit_code() {
+        let options = Options::new();
+        assert_eq!(1, DefaultParser::builder().build().parse_args(&options, &[""; 0]).unwrap().get_exit_code());
+        let mut parser = DefaultParser::builder().set_usage_exit_code(2).build();
+        assert_eq!(2, parser.parse_args(&options, &[""; 0]).unwrap().get_exit_code());
+    }
+
+    #[test]
+    fn test_help_and_version_requested() {
+        let mut options = Options::new();
+        options.add_option2("h", "help", false, "print help").unwrap();
+        options.add_option2("V", "version", false, "print version").unwrap();
+        options.add_required_option("f", "file", true, "").unwrap();
+
+        let mut parser = DefaultParser::builder().set_auto_help(true).set_version("1.0").build();
+        assert_eq!(Err(ParseErr::HelpRequested), parser.parse_args(&options, &["-h"]).map(|_| ()));
+        assert_eq!(Err(ParseErr::VersionRequested), parser.parse_args(&options, &["--version", "-x"]).map(|_| ()));
+
+        let mut parser = DefaultParser::builder().build();
+        assert!(parser.parse_args(&options, &["-h", "-f", "a"]).is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_options() {
+        let mut options = Options::new();
+        options.add_option0("o", true, "").unwrap();
+        options.add_option0("v", false, "").unwrap();
+        options.add_option(AnpOption::builder().option("I").has_args().build().unwrap());
+
+        let args = ["-o", "a", "-v", "-v", "-I", "x", "-I", "y", "-o", "b"];
+        assert!(DefaultParser::builder().build().parse_args(&options, &args).is_ok());
+
+        let mut parser = DefaultParser::builder().set_allow_duplicate_options(false).build();
+        assert_eq!(Err(ParseErr::DuplicateOption("o".to_string())), parser.parse_args(&options, &args).map(|_| ()));
+        assert!(parser.parse_args(&options, &args[..8]).is_ok());
+
+        // only options taking unlimited values and property options may be repeated
+        options.add_option(AnpOption::builder().option("p").number_of_args(2).build().unwrap());
+        options.add_option(AnpOption::builder().option("r").args_range(1, None).build().unwrap());
+        options.add_option(AnpOption::builder().option("D").property_option(true).build().unwrap());
+        assert!(parser.parse_args(&options, &["-r", "a", "-r", "b", "c"]).is_ok());
+        let cmd = parser.parse_args(&options, &["-Da=1", "-Db=2"]).unwrap();
+        assert_eq!(Some(&vec!["a", "1", "b", "2"].into_iter().map(String::from).collect()), cmd.to_value_map().get("D"));
+        assert_eq!(Err(ParseErr::DuplicateOption("p".to_string())),
+                   parser.parse_args(&options, &["-p", "a", "b", "-p", "c", "d"]).map(|_| ()));
+    }
+
+    #[test]
+    fn test_validate_paths() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("f").has_arg(true).value_hint(ValueHint::FilePath).build().unwrap());
+        options.add_option(AnpOption::builder().option("d").has_arg(true).value_hint(ValueHint::DirPath)
+            .default_value("out").build().unwrap());
+        options.add_option(AnpOption::builder().option("n").has_arg(true).build().unwrap());
+
+        let checker = |path: &Path, hint: ValueHint| match hint {
+            ValueHint::DirPath => path == Path::new("out") || path == Path::new("src"),
+            _ => path == Path::new("a.txt"),
+        };
+        let mut parser = DefaultParser::builder().set_validate_paths(true).set_path_checker(checker).build();
+        assert!(parser.parse_args(&options, &["-f", "a.txt", "-n", "b.txt"]).is_ok());
+        assert!(parser.parse_args(&options, &["-d", "src"]).is_ok());
+        assert_eq!(Err(ParseErr::InvalidPath { option: "f".to_string(), path: "b.txt".to_string() }),
+                   parser.parse_args(&options, &["-f", "b.txt"]).map(|_| ()));
+        assert_eq!(Err(ParseErr::InvalidPath { option: "d".to_string(), path: "a.txt".to_string() }),
+                   parser.parse_args(&options, &["-d", "a.txt"]).map(|_| ()));
+
+        let mut parser = DefaultParser::builder().set_path_checker(checker).build();
+        assert!(parser.parse_args(&options, &["-f", "b.txt"]).is_ok());
+    }
+
+    #[test]
+    fn test_help_aliases() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().long_option("help").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("host").has_arg(true).build().unwrap());
+
+        let mut parser = DefaultParser::builder().set_auto_help(true).build();
+        assert!(matches!(parser.parse_args(&options, &["--h"]), Err(ParseErr::AmbiguousOption { .. })));
+        assert_eq!(Err(ParseErr::HelpRequested), parser.parse_args(&options, &["--he"]).map(|_| ()));
+
+        let mut parser = DefaultParser::builder().set_auto_help(true).set_help_aliases(&["--help", "-?"]).build();
+        assert_eq!(Err(ParseErr::HelpRequested), parser.parse_args(&options, &["-?"]).map(|_| ()));
+        assert_eq!(Err(ParseErr::HelpRequested), parser.parse_args(&options, &["--host", "a", "--help"]).map(|_| ()));
+        assert!(parser.parse_args(&options, &["--he"]).unwrap().has_option("help"));
+    }
+
+    #[test]
+    fn test_eager_option() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("f").long_option("file").required(true).has_arg(true).build().unwrap());
+        options.add_option(AnpOption::builder().option("h").long_option("help").eager(true).build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        assert!(parser.parse_args(&options, &["--help"]).unwrap().has_option("help"));
+        assert!(parser.parse_args(&options, &["--file", "--help"]).unwrap().has_option("help"));
+        assert!(matches!(parser.parse_args(&options, &[] as &[&str]), Err(ParseErr::MissingOption(_))));
+    }
+
+    #[test]
+    fn test_concatenated_options() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("c").build().unwrap());
+        options.add_option(AnpOption::builder().option("z").build().unwrap());
+        options.add_option(AnpOption::builder().option("f").has_arg(true).build().unwrap());
+
+        let mut parser = DefaultParser::builder().build();
+        for args in [["-czf", "name"], ["-zcf", "name"]] {
+            let cmd = parser.parse_args(&options, &args).unwrap();
+            assert!(cmd.has_option("c") && cmd.has_option("z"));
+            assert_eq!(Some(Ok("name".to_string())), cmd.get_value("f"));
+            assert!(cmd.get_arg_list().is_empty());
         }
 
-        self.check_required_args()?;
+        let cmd = parser.parse_args(&options, &["-cfzé", "x"]).unwrap();
+        assert!(!cmd.has_option("z"));
+        assert_eq!(Some(Ok("zé".to_string())), cmd.get_value("f"));
+        assert_eq!(vec!["x"], cmd.get_arg_list());
+
+        let cmd = parser.parse_args(&options, &["-f", "-é"]).unwrap();
+        assert_eq!(Some(Ok("-é".to_string())), cmd.get_value("f"));
 
-        self.handle_defaults()?;
+        let mut parser = DefaultParser::builder().set_stop_at_non_option(true).build();
+        let cmd = parser.parse_args(&options, &["-cé", "-z"]).unwrap();
+        assert!(cmd.has_option("c") && !cmd.has_option("z"));
+        assert_eq!(vec!["é", "-z"], cmd.get_arg_list());
+    }
 
-        self.check_required_options()?;
+    #[test]
+    fn test_trailing_var_arg() {
+        let mut options = Options::new();
+        options.add_option(AnpOption::builder().option("v").build().unwrap());
+        options.add_option(AnpOption::builder().long_option("exec").trailing_var_arg(true).build().unwrap());
+        assert!(options.try_add_option(AnpOption::builder().long_option("run").trailing_var_arg(true).build().unwrap()).is_err());
+
+        let mut parser = DefaultParser::builder().build();
+        let cmd = parser.parse_args(&options, &["--exec", "cmd", "-v", "--flag", "--", "extra"]).unwrap();
+        assert!(!cmd.has_option("v"));
+        assert_eq!(Some(&vec!["cmd", "-v", "--flag", "--", "extra"].into_iter().map(String::from).collect()),
+                   cmd.to_value_map().get("exec"));
+        assert!(cmd.get_arg_list().is_empty());
+
+        options.add_option(AnpOption::builder().long_option("run").trailing_var_arg(true).build().unwrap());
+        assert_eq!(Some(ParseErr::ProcessingErr {
+            desc: "options 'exec' and 'run' are both trailing var arg options".to_string(),
+            source: None,
+        }), parser.parse_args(&options, &["-v"]).err());
+    }
+
+    /// A parser implementing only the required methods of [`Parser`].
+    struct RecordingParser(Vec<String>);
+
+    impl Parser for RecordingParser {
+        fn parse(&mut self, options: &Options) -> Result<CommandLine, ParseErr> {
+            self.parse_args(options, &[] as &[&str])
+        }
+
+        fn parse_or_exit(&mut self, options: &Options, _: &HelpFormatter) -> CommandLine {
+            self.parse(options).unwrap()
+        }
+
+        fn parse_args<T: ToString>(&mut self, _: &Options, arguments: &[T]) -> Result<CommandLine, ParseErr> {
+            self.0 = arguments.iter().map(|a| a.to_string()).collect();
+            Ok(CommandLine::builder().build())
+        }
+    }
+
+    #[test]
+    fn test_default_parse_args_os() {
+        use std::ffi::OsString;
+
+        let mut parser = RecordingParser(vec![]);
+        parser.parse_args_os(&Options::new(), &[OsString::from("-f"), OsString::from("a.txt")]).unwrap();
+        assert_eq!(vec!["-f", "a.txt"], parser.0);
+
+        parser.parse_iter(&Options::new(), ["-x", "b"].iter()).unwrap();
+        assert_eq!(vec!["-x", "b"], parser.0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_args_os_borrowed() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut options = Options::new();
+        options.add_option0("f", true, "").unwrap();
+        let arguments = [OsStr::new("-f"), OsStr::from_bytes(b"a\xffb"), OsStr::new("arg")];
+
+        let mut parser = DefaultParser::builder().build();
+        assert_eq!(Some(ParseErr::InvalidUtf8Argument(1)), parser.parse_args_os_slice(&options, &arguments).err());
+
+        let mut parser = DefaultParser::builder().set_os_lossy(true).build();
+        let cmd = parser.parse_args_os_slice(&options, &arguments).unwrap();
+        assert_eq!(Some("a\u{FFFD}b".to_string()), cmd.get_value::<String>("f").map(|v| v.unwrap()));
+        assert_eq!(vec!["arg"], cmd.get_arg_list());
+    }
 
-        Ok(self.cmd.take().unwrap())
+    #[test]
+    fn test_positional() {
+        let options = Options::new();
+        let cmd = DefaultParser::builder().build().parse_args(&options, &[""; 0]).unwrap();
+        assert_eq!(None, cmd.positional(0));
+        assert!(cmd.positional_range(0).is_empty());
+        assert!(cmd.positional_range(1).is_empty());
+
+        let cmd = DefaultParser::builder().set_skip_program_name(true).build()
+            .parse_args(&options, &["app", "a", "b"]).unwrap();
+        assert_eq!(Some("a"), cmd.positional(0));
+        assert_eq!(Some("b"), cmd.positional(1));
+        assert_eq!(None, cmd.positional(2));
+        assert_eq!(["b"], cmd.positional_range(1));
+        assert!(cmd.positional_range(2).is_empty());
+        assert!(cmd.positional_range(usize::MAX).is_empty());
     }
 }
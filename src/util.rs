@@ -4,13 +4,120 @@ pub struct Util;
 
 impl Util {
     pub fn strip_leading_and_trailing_quotes(string: &str) -> &str {
-        let len = string.len();
-        if len > 1 && string.starts_with('"') && string.ends_with('"') {
-            if !(&string[1..len - 1]).contains('"') {
-                return &string[1..len - 1];
+        Self::strip_quotes_with(string, &['"'])
+    }
+
+    /// Strip the matching leading and trailing quote if it's one of `quotes`, unless
+    /// the same quote also appears inside the string.
+    pub fn strip_quotes_with<'a>(string: &'a str, quotes: &[char]) -> &'a str {
+        for &quote in quotes {
+            let len = quote.len_utf8();
+            if string.len() > len && string.starts_with(quote) && string.ends_with(quote) {
+                let inner = &string[len..string.len() - len];
+                if !inner.contains(quote) {
+                    return inner;
+                }
+            }
+        }
+        string
+    }
+
+    /// Quote and escape `string` as a JSON string literal.
+    pub fn escape_json(string: &str) -> String {
+        let mut buf = String::with_capacity(string.len() + 2);
+        buf.push('"');
+        for c in string.chars() {
+            match c {
+                '"' => buf.push_str("\\\""),
+                '\\' => buf.push_str("\\\\"),
+                '\n' => buf.push_str("\\n"),
+                '\r' => buf.push_str("\\r"),
+                '\t' => buf.push_str("\\t"),
+                c if (c as u32) < 0x20 => buf.push_str(&format!("\\u{:04x}", c as u32)),
+                c => buf.push(c),
+            }
+        }
+        buf.push('"');
+        buf
+    }
+
+    /// Escape `string` as roff text, the backslashes are escaped and the lines starting
+    /// with `.` or `'` are guarded so they are not taken as requests.
+    pub fn escape_roff(string: &str) -> String {
+        string.replace('\\', "\\e")
+            .split('\n')
+            .map(|line| if line.starts_with(['.', '\'']) { format!("\\&{}", line) } else { line.to_owned() })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Quote `string` as a single-quoted shell word.
+    pub fn quote_shell(string: &str) -> String {
+        format!("'{}'", string.replace('\'', "'\\''"))
+    }
+
+    /// Split the content of an argument file into tokens.
+    ///
+    /// Tokens are separated by whitespaces, unless the whitespaces are within double quotes.
+    /// The leading and trailing quotes of each token are stripped.
+    pub fn split_arg_file(content: &str) -> Vec<String> {
+        let mut tokens = vec![];
+        let mut token = String::new();
+        let mut in_quotes = false;
+        let mut has_token = false;
+        for c in content.chars() {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            } else if c.is_whitespace() && !in_quotes {
+                if has_token {
+                    tokens.push(Self::strip_leading_and_trailing_quotes(&token).to_owned());
+                    token.clear();
+                    has_token = false;
+                }
+                continue;
             }
+            token.push(c);
+            has_token = true;
+        }
+        if has_token {
+            tokens.push(Self::strip_leading_and_trailing_quotes(&token).to_owned());
+        }
+        tokens
+    }
+
+    /// Check if `string` is an integer, a float or a hex number, optionally signed.
+    ///
+    /// For example, `5`, `-5.0`, `5e2`, `-.5` and `0x10`, but not `inf` or `NaN`.
+    pub fn is_number(string: &str) -> bool {
+        let unsigned = string.strip_prefix(['-', '+']).unwrap_or(string);
+        if let Some(hex) = unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")) {
+            return !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit());
         }
-        return string;
+        unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') && unsigned.parse::<f64>().is_ok()
+    }
+
+    /// Parse a boolean from `true/false`, `yes/no`, `on/off` or `1/0`, ignoring case.
+    pub fn parse_bool(string: &str) -> Option<bool> {
+        match string.to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The Levenshtein distance between `a` and `b`, counted in chars.
+    pub fn edit_distance(a: &str, b: &str) -> usize {
+        let b: Vec<char> = b.chars().collect();
+        let mut prev: Vec<usize> = (0..=b.len()).collect();
+        for (i, ca) in a.chars().enumerate() {
+            let mut cur = vec![i + 1; b.len() + 1];
+            for (j, cb) in b.iter().enumerate() {
+                let cost = if ca == *cb { 0 } else { 1 };
+                cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+            }
+            prev = cur;
+        }
+        prev[b.len()]
     }
 
     pub fn strip_leading_hyphens(string: &str) -> &str {
@@ -54,12 +161,42 @@ impl OptionValidator {
         }
         Ok(())
     }
+
+    /// Validate the long option name, which can't be blank, start with `-`, or contain
+    /// whitespaces or `=`.
+    pub fn validate_long(long_option: &str) -> Result<(), OptionErr> {
+        if long_option.is_empty() {
+            return Err(OptionErr::of(None, "illegal blank long option name"));
+        }
+        if long_option.starts_with('-') {
+            return Err(OptionErr::of(None,
+                                     &format!("the long option '{}' cannot start with '-'", long_option)));
+        }
+        if let Some(c) = long_option.chars().find(|c| c.is_whitespace() || *c == '=') {
+            return Err(OptionErr::of(None,
+                                     &format!("the long option '{}' contains an illegal character: '{}'", long_option, c)));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::util::{OptionValidator, Util};
 
+    #[test]
+    fn test_strip_quotes_with() {
+        let quotes = ['"', '\''];
+        assert_eq!("text", Util::strip_quotes_with("'text'", &quotes));
+        assert_eq!("text", Util::strip_quotes_with("\"text\"", &quotes));
+        assert_eq!("'text\"", Util::strip_quotes_with("'text\"", &quotes));
+        assert_eq!("'te'xt'", Util::strip_quotes_with("'te'xt'", &quotes));
+        assert_eq!("te\"xt", Util::strip_quotes_with("'te\"xt'", &quotes));
+        assert_eq!("'", Util::strip_quotes_with("'", &quotes));
+        assert_eq!("'text'", Util::strip_quotes_with("'text'", &['"']));
+        assert_eq!("text", Util::strip_quotes_with("«text«", &['«']));
+    }
+
     #[test]
     fn test_strip_leading_and_trailing_quotes() {
         assert_eq!("text", Util::strip_leading_and_trailing_quotes("\"text\""));
@@ -78,6 +215,43 @@ mod test {
         assert_eq!("", Util::strip_leading_hyphens(""));
     }
 
+    #[test]
+    fn test_escape_json() {
+        assert_eq!("\"text\"", Util::escape_json("text"));
+        assert_eq!("\"\\\"quoted\\\"\"", Util::escape_json("\"quoted\""));
+        assert_eq!("\"a\\\\b\"", Util::escape_json("a\\b"));
+        assert_eq!("\"line\\nbreak\\t\"", Util::escape_json("line\nbreak\t"));
+        assert_eq!("\"\\u0001\"", Util::escape_json("\u{1}"));
+        assert_eq!("\"é→\"", Util::escape_json("é→"));
+    }
+
+    #[test]
+    fn test_quote_shell() {
+        assert_eq!("'text'", Util::quote_shell("text"));
+        assert_eq!("'two words'", Util::quote_shell("two words"));
+        assert_eq!("'it'\\''s'", Util::quote_shell("it's"));
+        assert_eq!("'$HOME'", Util::quote_shell("$HOME"));
+    }
+
+    #[test]
+    fn test_split_arg_file() {
+        assert_eq!(vec!["-a", "--file", "x.txt"], Util::split_arg_file("-a --file\n  x.txt\n"));
+        assert_eq!(vec!["-m", "hello world", "b"], Util::split_arg_file("-m \"hello world\" b"));
+        assert_eq!(vec!["--name=\"a b\""], Util::split_arg_file("--name=\"a b\""));
+        assert_eq!(vec![""], Util::split_arg_file("\"\""));
+        assert!(Util::split_arg_file(" \n\t").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(0, Util::edit_distance("verbose", "verbose"));
+        assert_eq!(2, Util::edit_distance("verbsoe", "verbose"));
+        assert_eq!(1, Util::edit_distance("verbos", "verbose"));
+        assert_eq!(1, Util::edit_distance("verbosee", "verbose"));
+        assert_eq!(3, Util::edit_distance("", "abc"));
+        assert_eq!(1, Util::edit_distance("café", "cafe"));
+    }
+
     #[test]
     fn test_option_validator() {
         assert!(OptionValidator::validate("").is_err());
@@ -94,4 +268,20 @@ mod test {
         assert!(OptionValidator::validate("@ok").is_ok());
         assert!(OptionValidator::validate("o8k").is_err());
     }
+
+    #[test]
+    fn test_option_validator_long() {
+        assert!(OptionValidator::validate_long("").is_err());
+        assert!(OptionValidator::validate_long("foo bar").is_err());
+        assert!(OptionValidator::validate_long("foo\tbar").is_err());
+        assert!(OptionValidator::validate_long("=x").is_err());
+        assert!(OptionValidator::validate_long("foo=bar").is_err());
+        assert!(OptionValidator::validate_long("-foo").is_err());
+        assert!(OptionValidator::validate_long("--foo").is_err());
+        assert!(OptionValidator::validate_long("foo").is_ok());
+        assert!(OptionValidator::validate_long("log-level").is_ok());
+        assert!(OptionValidator::validate_long("dry_run2").is_ok());
+        assert!(OptionValidator::validate_long("größe").is_ok());
+        assert!(OptionValidator::validate_long("長さ").is_ok());
+    }
 }